@@ -0,0 +1,384 @@
+use crate::config::{MemoryConfig, MemoryConfigParsed, check_psi_thresholds};
+use crate::config_error::{ConfigError, LimitPool};
+use crate::meminfo::read_from_start;
+use crate::psi::{self, PsiConfig, PsiConfigParsed};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// cgroup v2 unified hierarchy mount point
+pub const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+// A single number (or "max") and a newline
+const VALUE_BUFFER_SIZE: usize = 32;
+
+/// A cgroup watched on its own, e.g. a `user@.service` slice or a container with a `memory.max` limit.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CgroupConfig {
+    /// Path relative to the cgroup2 mount, as shown in /proc/<pid>/cgroup
+    /// (e.g. "/user.slice/user-1000.slice/user@1000.service").
+    pub path: String,
+    /// Thresholds evaluated against `memory.max - memory.current`.
+    pub memory: Option<MemoryConfig>,
+    /// Thresholds evaluated against the cgroup's `memory.pressure`.
+    pub psi: Option<PsiConfig>,
+}
+
+impl CgroupConfig {
+    pub fn is_effectively_empty(&self) -> bool {
+        self.memory
+            .as_ref()
+            .is_none_or(|m| m.is_effectively_empty())
+            && self.psi.as_ref().is_none_or(|p| p.is_effectively_empty())
+    }
+}
+
+#[derive(Debug)]
+pub struct CgroupConfigParsed {
+    pub path: String, // As configured, used to tag events
+    pub dir: PathBuf,
+    pub files: MemoryFiles,
+    pub memory: Option<MemoryConfigParsed>,
+    pub psi: Option<PsiConfigParsed>,
+}
+
+impl CgroupConfigParsed {
+    /// `index` is the entry's position in `cgroups`, for error labels ("cgroups[1].memory").
+    pub fn try_from_config(
+        config: CgroupConfig,
+        index: usize,
+        global_interval: u64,
+    ) -> Result<Self, Vec<ConfigError>> {
+        let dir = cgroup_dir(&config.path);
+        let invalid = |reason: String| vec![ConfigError::Cgroup(config.path.clone(), reason)];
+
+        let unreadable = |e: io::Error| {
+            invalid(format!(
+                "cannot read memory.current in {:?} (is it a cgroup v2 directory with the memory controller?): {}",
+                dir, e
+            ))
+        };
+        let files = MemoryFiles::open(&dir).map_err(unreadable)?;
+        let (_current, max) = files.read_usage().map_err(unreadable)?;
+
+        let memory = match config.memory.filter(|m| !m.is_effectively_empty()) {
+            Some(m) => {
+                if max.is_none() {
+                    return Err(invalid(
+                        "memory thresholds are set but memory.max is unlimited".to_string(),
+                    ));
+                }
+                let section = format!("cgroups[{}].memory", index);
+                let total = max.map(|max| (max, LimitPool::CgroupMax));
+                Some(MemoryConfigParsed::try_from_config(m, &section, total)?)
+            }
+            None => None,
+        };
+
+        let psi = match config.psi.filter(|p| !p.is_effectively_empty()) {
+            Some(p) => {
+                let parsed = PsiConfigParsed::try_from_config(p, global_interval)
+                    .map_err(|e| invalid(e.to_string()))?;
+                check_psi_thresholds(&parsed, &format!("cgroups[{}].psi", index))
+                    .map_err(|e| vec![e])?;
+                psi::validate_psi_availability(&parsed, &dir.join("memory.pressure"))
+                    .map_err(|e| invalid(format!("memory.pressure is not valid: {}", e)))?;
+                Some(parsed)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            path: config.path,
+            dir,
+            files,
+            memory,
+            psi,
+        })
+    }
+}
+
+/// Resolves a configured cgroup path to its directory under the cgroup2 mount.
+pub fn cgroup_dir(path: &str) -> PathBuf {
+    if Path::new(path).starts_with(CGROUP_ROOT) {
+        PathBuf::from(path)
+    } else {
+        Path::new(CGROUP_ROOT).join(path.trim_start_matches('/'))
+    }
+}
+
+/// A cgroup's memory.current, memory.max and memory.swap.current, kept open so that they are
+/// re-read without allocating (see `meminfo::read_from_start`).
+#[derive(Debug)]
+pub struct MemoryFiles {
+    current: File,
+    max: File,
+    swap: Option<File>, // Absent without swap accounting
+}
+
+impl MemoryFiles {
+    pub fn open(dir: &Path) -> io::Result<Self> {
+        Ok(Self {
+            current: File::open(dir.join("memory.current"))?,
+            max: File::open(dir.join("memory.max"))?,
+            swap: File::open(dir.join("memory.swap.current")).ok(),
+        })
+    }
+
+    /// Returns (memory.current, memory.max), where a max of "max" (unlimited) is None.
+    pub fn read_usage(&self) -> io::Result<(u64, Option<u64>)> {
+        let mut buffer = [0; VALUE_BUFFER_SIZE];
+        let current = parse_u64(read_value(&self.current, &mut buffer)?)?;
+        let max = parse_memory_max(read_value(&self.max, &mut buffer)?)?;
+        Ok((current, max))
+    }
+
+    /// memory.swap.current: swap charged to the cgroup.
+    pub fn read_swap(&self) -> io::Result<u64> {
+        let swap = self.swap.as_ref().ok_or(io::ErrorKind::NotFound)?;
+        parse_u64(read_value(swap, &mut [0; VALUE_BUFFER_SIZE])?)
+    }
+}
+
+fn read_value<'a>(file: &File, buffer: &'a mut [u8]) -> io::Result<&'a str> {
+    let len = read_from_start(file, buffer)?;
+    std::str::from_utf8(&buffer[..len]).map_err(io::Error::other)
+}
+
+fn parse_memory_max(s: &str) -> io::Result<Option<u64>> {
+    match s.trim() {
+        "max" => Ok(None),
+        s => s.parse::<u64>().map(Some).map_err(io::Error::other),
+    }
+}
+
+/// Free bytes and percent free under a `memory.max` limit; None for a limit of 0.
+pub fn headroom(current: u64, max: u64) -> Option<(u64, f64)> {
+    if max == 0 {
+        return None;
+    }
+    let free = max.saturating_sub(current);
+    Some((free, (free as f64 / max as f64) * 100.0))
+}
+
+fn parse_u64(s: &str) -> io::Result<u64> {
+    s.trim().parse::<u64>().map_err(io::Error::other)
+}
+
+/// An entry of `killScope`: a cgroup path ("/user.slice/user-1000.slice") or glob
+/// ("app-*.scope"). Matches processes whose cgroup lies at or below a matching cgroup.
+/// Leading '/' anchors the pattern at the root; otherwise it may match at any depth.
+/// `*` and `?` match within a single path component.
+#[derive(Debug, Clone)]
+pub struct ScopePattern {
+    anchored: bool,
+    components: Vec<String>,
+}
+
+impl ScopePattern {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return None;
+        }
+        Some(Self {
+            anchored: raw.starts_with('/'),
+            components: components(raw).map(str::to_string).collect(),
+        })
+    }
+
+    /// Scope for a monitored cgroup, so kills it triggers stay inside it.
+    pub fn for_cgroup(path: &str) -> Self {
+        let relative = path.strip_prefix(CGROUP_ROOT).unwrap_or(path);
+        Self {
+            anchored: true,
+            components: components(relative).map(str::to_string).collect(),
+        }
+    }
+
+    /// Allocation-free: `cgroup_path` is borrowed straight from the read buffer.
+    pub fn matches(&self, cgroup_path: &str) -> bool {
+        let mut start = components(cgroup_path);
+        if self.anchored {
+            return self.matches_prefix(start);
+        }
+        loop {
+            if self.matches_prefix(start.clone()) {
+                return true;
+            }
+            if start.next().is_none() {
+                return false;
+            }
+        }
+    }
+
+    fn matches_prefix<'a>(&self, mut path: impl Iterator<Item = &'a str>) -> bool {
+        self.components.iter().all(|pat| {
+            path.next()
+                .is_some_and(|c| glob_match(pat.as_bytes(), c.as_bytes()))
+        })
+    }
+}
+
+fn components(path: &str) -> impl Iterator<Item = &str> + Clone {
+    path.split('/').filter(|c| !c.is_empty())
+}
+
+/// Whether a cgroup is a single application's unit, safe to kill or freeze as a whole:
+/// an `app-*.scope` or a `*.service` other than a user manager (`user@UID.service`).
+/// Login sessions (`session-*.scope`), `init.scope` and slices never are.
+pub fn is_app_unit(path: &str) -> bool {
+    let Some(leaf) = components(path).last() else {
+        return false;
+    };
+    glob_match(b"app-*.scope", leaf.as_bytes())
+        || (leaf.ends_with(".service") && !leaf.starts_with("user@"))
+}
+
+/// Extracts the cgroup v2 path from the contents of /proc/<pid>/cgroup ("0::/path").
+pub fn unified_cgroup_path(content: &[u8]) -> Option<&str> {
+    let s = std::str::from_utf8(content).ok()?;
+    s.lines()
+        .find_map(|l| l.strip_prefix("0::"))
+        .map(str::trim_end)
+}
+
+/// Glob with '*' (any run) and '?' (any single byte), iterative with single backtrack point.
+pub fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == b'?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((sp, st)) = star {
+            p = sp + 1;
+            t = st + 1;
+            star = Some((sp, st + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cgroup_dir_accepts_relative_and_mounted_paths() {
+        let expected = PathBuf::from("/sys/fs/cgroup/user.slice/user-1000.slice");
+        assert_eq!(cgroup_dir("/user.slice/user-1000.slice"), expected);
+        assert_eq!(cgroup_dir("user.slice/user-1000.slice"), expected);
+        assert_eq!(
+            cgroup_dir("/sys/fs/cgroup/user.slice/user-1000.slice"),
+            expected
+        );
+    }
+
+    #[test]
+    fn memory_max_is_unlimited_or_bytes() {
+        assert_eq!(parse_memory_max("max\n").unwrap(), None);
+        assert_eq!(parse_memory_max("1073741824\n").unwrap(), Some(1 << 30));
+        assert!(parse_memory_max("lots\n").is_err());
+    }
+
+    #[test]
+    fn memory_files_are_reread_in_place() {
+        let dir = std::env::temp_dir().join(format!("ram-sentinel-cgroup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("memory.current"), "1024\n").unwrap();
+        std::fs::write(dir.join("memory.max"), "max\n").unwrap();
+        let files = MemoryFiles::open(&dir).unwrap();
+        assert_eq!(files.read_usage().unwrap(), (1024, None));
+        // No swap accounting
+        assert!(files.read_swap().is_err());
+
+        std::fs::write(dir.join("memory.current"), "768\n").unwrap();
+        std::fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+        assert_eq!(files.read_usage().unwrap(), (768, Some(1 << 30)));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn headroom_below_memory_max() {
+        assert_eq!(headroom(768, 1024), Some((256, 25.0)));
+        // Usage can briefly overshoot the limit
+        assert_eq!(headroom(2048, 1024), Some((0, 0.0)));
+        assert_eq!(headroom(0, 0), None);
+    }
+
+    #[test]
+    fn glob_match_wildcards() {
+        assert!(glob_match(b"app-*.scope", b"app-firefox-1234.scope"));
+        assert!(glob_match(b"app-*.scope", b"app-.scope"));
+        assert!(glob_match(b"user-????.slice", b"user-1000.slice"));
+        assert!(glob_match(b"*", b""));
+        assert!(glob_match(b"a*b*c", b"axxbyyc"));
+        assert!(!glob_match(b"app-*.scope", b"app-firefox.service"));
+        assert!(!glob_match(b"user-????.slice", b"user-100.slice"));
+        assert!(!glob_match(b"abc", b"abcd"));
+    }
+
+    #[test]
+    fn glob_match_backtracks() {
+        assert!(glob_match(b"*.scope", b"a.scope.scope"));
+        assert!(glob_match(b"*ab", b"aaab"));
+        assert!(!glob_match(b"*ab", b"aaba"));
+    }
+
+    #[test]
+    fn unified_cgroup_path_finds_v2_line() {
+        let hybrid = b"4:memory:/user.slice\n0::/user.slice/app.scope\n1:name=systemd:/\n";
+        assert_eq!(unified_cgroup_path(hybrid), Some("/user.slice/app.scope"));
+        assert_eq!(unified_cgroup_path(b"0::/\n"), Some("/"));
+        assert_eq!(unified_cgroup_path(b"4:memory:/x\n"), None);
+        assert_eq!(unified_cgroup_path(b"0::/\xff"), None);
+    }
+
+    #[test]
+    fn anchored_scope_matches_subtree() {
+        let scope = ScopePattern::parse("/user.slice/user-1000.slice").unwrap();
+        assert!(scope.matches("/user.slice/user-1000.slice"));
+        assert!(scope.matches("/user.slice/user-1000.slice/app.slice/app-x.scope"));
+        assert!(!scope.matches("/user.slice/user-1001.slice"));
+        assert!(!scope.matches("/system.slice/user.slice/user-1000.slice"));
+    }
+
+    #[test]
+    fn unanchored_scope_matches_at_any_depth() {
+        let scope = ScopePattern::parse("app-*.scope").unwrap();
+        assert!(scope.matches("/user.slice/user-1000.slice/app.slice/app-x.scope"));
+        assert!(scope.matches("/app-x.scope/child"));
+        assert!(!scope.matches("/user.slice/session-2.scope"));
+        assert!(ScopePattern::parse("  ").is_none());
+    }
+
+    #[test]
+    fn scope_for_cgroup_strips_mount_point() {
+        let scope = ScopePattern::for_cgroup("/sys/fs/cgroup/user.slice");
+        assert!(scope.matches("/user.slice/app.scope"));
+        assert!(!scope.matches("/system.slice"));
+    }
+
+    #[test]
+    fn app_units_only() {
+        assert!(is_app_unit(
+            "/user.slice/user-1000.slice/user@1000.service/app.slice/app-firefox-12.scope"
+        ));
+        assert!(is_app_unit("/system.slice/nginx.service"));
+        assert!(!is_app_unit(
+            "/user.slice/user-1000.slice/user@1000.service"
+        ));
+        assert!(!is_app_unit("/user.slice/user-1000.slice/session-2.scope"));
+        assert!(!is_app_unit("/init.scope"));
+        assert!(!is_app_unit("/user.slice/user-1000.slice"));
+        assert!(!is_app_unit("/"));
+    }
+}
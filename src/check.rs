@@ -0,0 +1,418 @@
+use crate::config::{Config, KillAccounting, KillStrategy, Pattern, line_column};
+use crate::config_error::ConfigError;
+use crate::matcher::MAX_COMM_LEN;
+use crate::meminfo::MemInfoReader;
+use crate::psi::{PsiConfig, PsiConfigParsed};
+use crate::utils::parse_size;
+use byte_unit::Byte;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Below this, a PSI kill barely dents the pressure and the sequence keeps re-triggering
+const MIN_SENSIBLE_AMOUNT_TO_FREE: u64 = 32 * 1024 * 1024;
+
+const OWN_NAME: &str = "ram-sentinel";
+
+/// A legal but probably unintended setting.
+struct Warning {
+    path: Vec<&'static str>, // Key path used to locate the setting in the file
+    index: Option<usize>,    // Position within a list, for locating the entry
+    text: String,
+}
+
+/// `ram-sentinel check-config [FILE]`: loads, validates and compiles the configuration exactly
+/// like startup does, but reports every problem instead of the first. Returns the exit code
+/// startup would fail with (that of the first error), or 0 if the config is usable.
+pub fn run(file: Option<PathBuf>) -> i32 {
+    let (config, source_path) = match Config::read(file) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            let path = match &e {
+                ConfigError::FileRead(path, _)
+                | ConfigError::FileParse(path, ..)
+                | ConfigError::ConfigFileNotFound(path) => Some(path.as_path()),
+                _ => None,
+            };
+            print_problem(path, e.location(), "error", &e.to_string());
+            println!("1 error(s), 0 warning(s)");
+            return e.exit_code();
+        }
+    };
+
+    let content = source_path
+        .as_deref()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .unwrap_or_default();
+    let path = source_path.as_deref();
+    let warnings = lint(&config);
+    let errors = match config.into_runtime(source_path.clone()) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors,
+    };
+
+    for e in &errors {
+        let location = e.location().or_else(|| locate_error(&content, e));
+        print_problem(path, location, "error", &e.to_string());
+    }
+    for w in &warnings {
+        let location = locate(&content, &w.path, w.index);
+        print_problem(path, location, "warning", &w.text);
+    }
+
+    let source = path.map_or("built-in defaults".to_string(), |p| format!("{:?}", p));
+    if errors.is_empty() && warnings.is_empty() {
+        println!("Configuration OK ({})", source);
+    } else {
+        println!(
+            "{} error(s), {} warning(s) in {}",
+            errors.len(),
+            warnings.len(),
+            source
+        );
+    }
+    errors.first().map_or(0, |e| e.exit_code())
+}
+
+fn print_problem(path: Option<&Path>, location: Option<(usize, usize)>, kind: &str, text: &str) {
+    let file = path.map_or("<defaults>".to_string(), |p| p.display().to_string());
+    match location {
+        Some((line, column)) => println!("{}:{}:{}: {}: {}", file, line, column, kind, text),
+        None => println!("{}: {}: {}", file, kind, text),
+    }
+}
+
+fn lint(config: &Config) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    if let Some(amount) = config
+        .psi
+        .as_ref()
+        .and_then(|p| p.amount_to_free.as_deref())
+        .and_then(parse_size)
+        && amount > 0
+        && amount < MIN_SENSIBLE_AMOUNT_TO_FREE
+    {
+        warnings.push(Warning {
+            path: vec!["psi", "amountToFree"],
+            index: None,
+            text: format!(
+                "psi.amountToFree ({}) is very small; a PSI-triggered kill may free too little to relieve pressure",
+                format_bytes(amount)
+            ),
+        });
+    }
+
+    // Kernel trigger windows are 500ms..10s; a longer interval silently measures a shorter span
+    let psi_sections = config
+        .psi
+        .iter()
+        .map(|p| (vec!["psi"], None, "psi".to_string(), p));
+    let cgroup_sections = config.cgroups.iter().enumerate().filter_map(|(i, c)| {
+        let psi = c.psi.as_ref()?;
+        Some((vec!["cgroups"], Some(i), format!("cgroups[{}].psi", i), psi))
+    });
+    for (path, index, section, psi) in psi_sections.chain(cgroup_sections) {
+        if let Some(window_ms) = clamped_trigger_window(psi, config.check_interval_ms) {
+            warnings.push(Warning {
+                path,
+                index,
+                text: format!(
+                    "{}: kernel PSI triggers measure stalls over {}ms (the kernel allows 500ms to 10s), not over checkIntervalMs; use mode 'sample' to measure over the full interval",
+                    section, window_ms
+                ),
+            });
+        }
+    }
+
+    // The killer never picks its own PID, but a match means other ram-sentinel instances
+    // (e.g. other users' daemons when running as root) are fair game
+    let own_cmdlines = own_cmdlines();
+    for (i, target) in config.kill_targets.iter().enumerate() {
+        // Entries checking more than the cmdline can't be judged without a running daemon
+        let matcher = target.matcher();
+        let Some(raw) = matcher.cmdline_only() else {
+            continue;
+        };
+        let Ok(pattern) = Pattern::parse(raw) else {
+            continue; // Reported as an error
+        };
+        if own_cmdlines.iter().any(|c| pattern.matches(c)) {
+            warnings.push(Warning {
+                path: vec!["killTargets"],
+                index: Some(i),
+                text: format!(
+                    "killTargets entry {} ('{}') matches ram-sentinel itself",
+                    i, raw
+                ),
+            });
+        }
+    }
+
+    // comm is truncated by the kernel; a longer literal silently never matches
+    let matchers = config
+        .kill_targets
+        .iter()
+        .map(|t| ("killTargets", t.matcher()))
+        .enumerate()
+        .chain(
+            config
+                .ignore_names
+                .iter()
+                .map(|n| ("ignoreNames", n.matcher()))
+                .enumerate(),
+        );
+    for (i, (list, matcher)) in matchers {
+        for (field, value) in matcher.overlong_comm_fields() {
+            warnings.push(Warning {
+                path: vec![list],
+                index: Some(i),
+                text: format!(
+                    "{} entry {}: {} '{}' is longer than a process name can be ({} characters), so it never matches",
+                    list, i, field, value, MAX_COMM_LEN
+                ),
+            });
+        }
+        // '/.../' is a regex, so a directory path written that way matches anywhere in the value
+        for (field, value) in matcher.path_like_regex_fields() {
+            warnings.push(Warning {
+                path: vec![list],
+                index: Some(i),
+                text: format!(
+                    "{} entry {}: {} '{}' is read as a regex, not a path (use '{}*' for everything under it)",
+                    list,
+                    i,
+                    field,
+                    value,
+                    value
+                ),
+            });
+        }
+    }
+
+    if config.kill_weights.is_some() && config.kill_strategy != KillStrategy::Weighted {
+        warnings.push(Warning {
+            path: vec!["killWeights"],
+            index: None,
+            text: "killWeights has no effect unless killStrategy is 'weighted'".to_string(),
+        });
+    }
+
+    // smaps_rollup needs Linux 4.14; without it the killer falls back to RSS
+    if config.kill_accounting != KillAccounting::Rss
+        && !Path::new("/proc/self/smaps_rollup").exists()
+    {
+        warnings.push(Warning {
+            path: vec!["killAccounting"],
+            index: None,
+            text: format!(
+                "killAccounting '{}' needs /proc/<pid>/smaps_rollup (Linux 4.14+); RSS is used instead",
+                config.kill_accounting.as_str()
+            ),
+        });
+    }
+
+    // Older kernels lack some fields (Zswap/Zswapped need 5.19); such limits never fire
+    if let Ok(info) = MemInfoReader::new().and_then(|mut r| r.read()) {
+        for field in config.meminfo.keys() {
+            if field.get(&info).is_none() {
+                warnings.push(Warning {
+                    path: vec!["meminfo", field.config_key()],
+                    index: None,
+                    text: format!(
+                        "meminfo.{}: this kernel does not report {} in /proc/meminfo; the limit is ignored",
+                        field.config_key(),
+                        field.as_str()
+                    ),
+                });
+            }
+        }
+    }
+    warnings
+}
+
+// None if the section is invalid, which is reported as an error
+fn clamped_trigger_window(psi: &PsiConfig, global_interval: u64) -> Option<u64> {
+    PsiConfigParsed::try_from_config(psi.clone(), global_interval)
+        .ok()?
+        .clamped_trigger_window()
+}
+
+// How a running daemon's cmdline typically reads, as seen by killTargets patterns
+fn own_cmdlines() -> Vec<String> {
+    let mut cmdlines = vec![OWN_NAME.to_string()];
+    if let Ok(exe) = env::current_exe() {
+        cmdlines.push(exe.display().to_string());
+    }
+    cmdlines
+}
+
+fn format_bytes(bytes: u64) -> String {
+    format!(
+        "{:.2}",
+        Byte::from_u64(bytes).get_appropriate_unit(byte_unit::UnitType::Decimal)
+    )
+}
+
+// Best-effort: points at the setting an error is about. Serde only reports positions for syntax
+// and type errors; everything found later is located by searching for its key.
+fn locate_error(content: &str, e: &ConfigError) -> Option<(usize, usize)> {
+    match e {
+        ConfigError::IntervalTooHigh(_) | ConfigError::IntervalTooLow(_) => {
+            locate(content, &["checkIntervalMs"], None)
+        }
+        ConfigError::PsiConfig(_) | ConfigError::PsiUnavailable(_) => {
+            locate(content, &["psi"], None)
+        }
+        ConfigError::RegexError(_, _, pattern, _) => find_word(content, pattern, 0),
+        ConfigError::InvalidSize(field, _)
+        | ConfigError::InvalidPercent(field, _)
+        | ConfigError::ThresholdOrder(_, _, field, _)
+        | ConfigError::ThresholdUnreachable(field, ..) => {
+            let keys: Vec<&str> = field.split('.').collect();
+            locate(content, &keys, None)
+        }
+        ConfigError::Cgroup(path, _) => find_word(content, path, 0),
+        ConfigError::Matcher(field, index, ..) => {
+            let key = if field == "kill_targets" {
+                "killTargets"
+            } else {
+                "ignoreNames"
+            };
+            locate(content, &[key], Some(*index))
+        }
+        ConfigError::KillWeights(_) => locate(content, &["killWeights"], None)
+            .or_else(|| locate(content, &["killStrategy"], None)),
+        _ => None,
+    }
+}
+
+/// Finds `path` (a chain of keys, each searched after the previous one) and optionally the
+/// `index`-th list item after it. A key may select a list item itself ("cgroups[1]").
+fn locate(content: &str, path: &[&str], index: Option<usize>) -> Option<(usize, usize)> {
+    let mut offset = 0;
+    for key in path {
+        let (name, item) = match key.strip_suffix(']').and_then(|k| k.split_once('[')) {
+            Some((name, item)) => (name, Some(item.parse().ok()?)),
+            None => (*key, None),
+        };
+        offset = find_word_offset(content, name, offset)?;
+        if let Some(item) = item {
+            offset = item_offset(content, offset, item)?;
+        }
+    }
+    if let Some(index) = index {
+        return Some(line_column(content, item_offset(content, offset, index)?));
+    }
+    Some(line_column(content, offset))
+}
+
+// Start of the `index`-th list item after the key at `offset`
+fn item_offset(content: &str, offset: usize, index: usize) -> Option<usize> {
+    // One item per line in all three formats as commonly written
+    let rest = &content[offset..];
+    let first_line_end = rest.find('\n')? + 1;
+    let mut items = rest[first_line_end..]
+        .lines()
+        .filter(|l| !l.trim().is_empty() && !l.trim_start().starts_with('#'))
+        .peekable();
+    // A YAML block item ("- ") may span several lines; count only its first
+    let marker_indent = items
+        .peek()
+        .filter(|l| l.trim_start().starts_with("- "))
+        .map(|l| l.len() - l.trim_start().len());
+    let indent_of = |l: &str| l.len() - l.trim_start().len();
+    let item_line = items
+        .take_while(|l| {
+            // The list ends at the first line outdented from its markers (or a key beside them)
+            marker_indent.is_none_or(|indent| {
+                indent_of(l) > indent || (indent_of(l) == indent && l.trim_start().starts_with('-'))
+            })
+        })
+        .filter(|l| marker_indent.is_none_or(|indent| indent_of(l) == indent))
+        .nth(index)?;
+    let item_offset = offset + first_line_end + rest[first_line_end..].find(item_line)?;
+    let indent = item_line.len() - item_line.trim_start().len();
+    Some(item_offset + indent)
+}
+
+fn find_word(content: &str, word: &str, from: usize) -> Option<(usize, usize)> {
+    find_word_offset(content, word, from).map(|offset| line_column(content, offset))
+}
+
+// Occurrence of `word` not embedded in a longer identifier
+fn find_word_offset(content: &str, word: &str, from: usize) -> Option<usize> {
+    if word.is_empty() {
+        return None;
+    }
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let mut start = from;
+    while let Some(pos) = content.get(start..)?.find(word) {
+        let at = start + pos;
+        let end = at + word.len();
+        let before_ok = !content[..at].chars().next_back().is_some_and(is_ident);
+        let after_ok = !content[end..].chars().next().is_some_and(is_ident);
+        if before_ok && after_ok {
+            return Some(at);
+        }
+        start = end;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const YAML: &str = "\
+ram:
+  killMinFreePercent: 5
+killTargets:
+  # comment
+  - type=renderer
+  - comm: sleep
+    killCgroup: true
+  - /npm/
+cgroups:
+  - path: /a
+    memory:
+      killMinFreeBytes: 1G
+  - path: /b
+    memory:
+      killMinFreeBytes: 2G
+";
+
+    #[test]
+    fn locates_nested_key() {
+        assert_eq!(
+            locate(YAML, &["ram", "killMinFreePercent"], None),
+            Some((2, 3))
+        );
+    }
+
+    #[test]
+    fn locates_list_items_spanning_lines() {
+        assert_eq!(locate(YAML, &["killTargets"], Some(0)), Some((5, 3)));
+        assert_eq!(locate(YAML, &["killTargets"], Some(1)), Some((6, 3)));
+        assert_eq!(locate(YAML, &["killTargets"], Some(2)), Some((8, 3)));
+        assert_eq!(locate(YAML, &["killTargets"], Some(3)), None);
+    }
+
+    #[test]
+    fn locates_key_inside_indexed_item() {
+        let keys = ["cgroups[1]", "memory", "killMinFreeBytes"];
+        assert_eq!(locate(YAML, &keys, None), Some((15, 7)));
+        assert_eq!(locate(YAML, &["cgroups[x]"], None), None);
+    }
+
+    #[test]
+    fn locates_items_one_per_line() {
+        let json = "{\n  \"killTargets\": [\n    \"a\",\n    \"b\"\n  ]\n}";
+        assert_eq!(locate(json, &["killTargets"], Some(1)), Some((4, 5)));
+    }
+
+    #[test]
+    fn find_word_skips_longer_identifiers() {
+        let content = "killMinFreeBytesX: 1\nkillMinFreeBytes: 2\n";
+        assert_eq!(find_word(content, "killMinFreeBytes", 0), Some((2, 1)));
+    }
+}
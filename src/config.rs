@@ -1,10 +1,17 @@
-use crate::config_error::ConfigError;
+use crate::cgroup::{CgroupConfig, CgroupConfigParsed, ScopePattern, glob_match};
+use crate::config_error::{ConfigError, LimitKind, LimitPool};
 use crate::events::{LogLevel, SentinelEvent};
 use crate::logging;
+use crate::matcher::{Matcher, MatcherConfig};
+use crate::meminfo::{MemInfoField, MemInfoLimit};
 use crate::psi;
-use crate::utils::parse_size;
+use crate::swap::effective_total_swap;
+use crate::utils::{get_total_memory, parse_size};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -15,6 +22,15 @@ pub struct Config {
     pub psi: Option<psi::PsiConfig>,
     pub ram: Option<MemoryConfig>,
     pub swap: Option<MemoryConfig>,
+    /// RAM and swap as one pool: MemAvailable + SwapFree against MemTotal + SwapTotal.
+    pub combined: Option<MemoryConfig>,
+    /// Limits on the RAM used by compressed swap (zram swap devices + the zswap pool).
+    pub compressed_swap: Option<UsageConfig>,
+    /// Upper limits on individual /proc/meminfo fields (e.g. `shmem`, `dirty`).
+    #[serde(default)]
+    pub meminfo: BTreeMap<MemInfoField, UsageConfig>,
+    #[serde(default)]
+    pub cgroups: Vec<CgroupConfig>,
 
     // Operational Settings
     #[serde(default = "default_interval")]
@@ -23,25 +39,53 @@ pub struct Config {
     pub warn_reset_ms: u64,
     #[serde(default = "sigterm_wait_ms")]
     pub sigterm_wait_ms: u64,
+    /// How long metrics must stay above warn thresholds before a suspended target is resumed.
+    #[serde(default = "thaw_dwell_ms")]
+    pub thaw_dwell_ms: u64,
+    /// How long a paused target gets to relieve memory before a renewed breach kills it.
+    #[serde(default = "pause_grace_ms")]
+    pub pause_grace_ms: u64,
 
     // Targeting Logic
     #[serde(default)]
-    pub ignore_names: Vec<String>,
+    pub ignore_names: Vec<IgnoreNameConfig>,
 
     #[serde(default = "default_kill_targets")]
-    pub kill_targets: Vec<String>,
+    pub kill_targets: Vec<KillTargetConfig>,
+
+    /// cgroup v2 paths or globs; when set, only processes inside them may be killed.
+    #[serde(default)]
+    pub kill_scope: Vec<String>,
 
     #[serde(default = "default_strategy")]
     pub kill_strategy: KillStrategy,
+
+    /// Factor weights for `killStrategy: weighted` (defaults: rss 1, targetIndex 1).
+    pub kill_weights: Option<KillWeights>,
+
+    /// How a victim's memory is sized: for `largestRss` ranking and the freed estimate.
+    #[serde(default)]
+    pub kill_accounting: KillAccounting,
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct MemoryConfig {
     pub warn_min_free_bytes: Option<String>,
     pub warn_min_free_percent: Option<f32>,
     pub kill_min_free_bytes: Option<String>,
     pub kill_min_free_percent: Option<f32>,
+    /// The kill condition must hold for this many consecutive checks...
+    pub kill_after_samples: Option<u32>,
+    /// ...and for this long before the kill sequence starts.
+    pub kill_after_ms: Option<u64>,
+    /// After a kill, the trigger stays disarmed until free memory is back above this.
+    pub rearm_min_free_bytes: Option<String>,
+    pub rearm_min_free_percent: Option<f32>,
+    /// Predictive limits: act when, at the recent rate of decline, free memory reaches the kill
+    /// limit (or zero) within this many milliseconds.
+    pub kill_when_exhausted_within_ms: Option<u64>,
+    pub warn_when_exhausted_within_ms: Option<u64>,
 }
 #[derive(Debug, Clone)]
 pub struct MemoryConfigParsed {
@@ -49,51 +93,398 @@ pub struct MemoryConfigParsed {
     pub warn_min_free_percent: Option<f32>,
     pub kill_min_free_bytes: Option<u64>,
     pub kill_min_free_percent: Option<f32>,
+    pub hold: KillHold,
+    pub rearm_min_free_bytes: Option<u64>,
+    pub rearm_min_free_percent: Option<f32>,
+    pub kill_when_exhausted_within_ms: Option<u64>,
+    pub warn_when_exhausted_within_ms: Option<u64>,
+}
+
+/// How long a kill condition must persist before it fires. The default fires on the first
+/// breaching sample.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KillHold {
+    pub samples: u32,
+    pub duration_ms: u64,
+}
+
+impl KillHold {
+    pub fn new(samples: Option<u32>, duration_ms: Option<u64>) -> Self {
+        Self {
+            samples: samples.unwrap_or(0),
+            duration_ms: duration_ms.unwrap_or(0),
+        }
+    }
+
+    pub fn satisfied(&self, samples: u32, held_ms: u64) -> bool {
+        samples >= self.samples && held_ms >= self.duration_ms
+    }
 }
 
 impl MemoryConfigParsed {
-    pub fn try_from_config(config: MemoryConfig) -> Result<Self, ConfigError> {
-        let warn_min_free_bytes = if let Some(s) = config.warn_min_free_bytes.as_ref() {
-            Some(parse_size(s).ok_or_else(|| {
-                ConfigError::InvalidSize("warnMinFreeBytes".to_string(), s.clone())
-            })?)
-        } else {
-            None
+    /// `section` prefixes field names in errors (e.g. "ram" gives "ram.killMinFreeBytes").
+    /// `total` is the memory the thresholds are measured against and which pool that is, when
+    /// known up front (RAM size, a cgroup's memory.max). Every invalid field is reported, not
+    /// just the first.
+    pub fn try_from_config(
+        config: MemoryConfig,
+        section: &str,
+        total: Option<(u64, LimitPool)>,
+    ) -> Result<Self, Vec<ConfigError>> {
+        let field = |name: &str| format!("{}.{}", section, name);
+        let mut errors = Vec::new();
+        let mut size = |name: &str, value: &Option<String>| {
+            let s = value.as_ref()?;
+            let parsed = parse_size(s);
+            if parsed.is_none() {
+                errors.push(ConfigError::InvalidSize(field(name), s.clone()));
+            }
+            parsed
+        };
+        let warn_min_free_bytes = size("warnMinFreeBytes", &config.warn_min_free_bytes);
+        let kill_min_free_bytes = size("killMinFreeBytes", &config.kill_min_free_bytes);
+        let rearm_min_free_bytes = size("rearmMinFreeBytes", &config.rearm_min_free_bytes);
+
+        for (name, percent) in [
+            ("warnMinFreePercent", config.warn_min_free_percent),
+            ("killMinFreePercent", config.kill_min_free_percent),
+            ("rearmMinFreePercent", config.rearm_min_free_percent),
+        ] {
+            if let Some(p) = percent
+                && !(0.0..=100.0).contains(&p)
+            {
+                errors.push(ConfigError::InvalidPercent(field(name), p));
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let parsed = Self {
+            warn_min_free_bytes,
+            warn_min_free_percent: config.warn_min_free_percent,
+            kill_min_free_bytes,
+            kill_min_free_percent: config.kill_min_free_percent,
+            hold: KillHold::new(config.kill_after_samples, config.kill_after_ms),
+            rearm_min_free_bytes,
+            rearm_min_free_percent: config.rearm_min_free_percent,
+            kill_when_exhausted_within_ms: config.kill_when_exhausted_within_ms,
+            warn_when_exhausted_within_ms: config.warn_when_exhausted_within_ms,
         };
+        parsed.check_thresholds(section, total)?;
+        Ok(parsed)
+    }
+
+    // Cross-field checks: limits must be reachable, warn must fire before kill,
+    // and re-arming must need more free memory than killing
+    fn check_thresholds(
+        &self,
+        section: &str,
+        total: Option<(u64, LimitPool)>,
+    ) -> Result<(), Vec<ConfigError>> {
+        let field = |name: &str| format!("{}.{}", section, name);
+        let mut errors = Vec::new();
+
+        if let Some((total, pool)) = total {
+            for (name, limit) in [
+                ("warnMinFreeBytes", self.warn_min_free_bytes),
+                ("killMinFreeBytes", self.kill_min_free_bytes),
+                ("rearmMinFreeBytes", self.rearm_min_free_bytes),
+            ] {
+                if let Some(limit) = limit
+                    && limit >= total
+                {
+                    errors.push(ConfigError::ThresholdUnreachable(
+                        field(name),
+                        limit,
+                        total,
+                        pool,
+                        LimitKind::MinFree,
+                    ));
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors); // Ordering against an impossible limit says nothing more
+        }
 
-        let kill_min_free_bytes = if let Some(s) = config.kill_min_free_bytes.as_ref() {
-            Some(parse_size(s).ok_or_else(|| {
-                ConfigError::InvalidSize("killMinFreeBytes".to_string(), s.clone())
-            })?)
+        // Bytes override percent (Strict Priority), so compare the limits actually in effect
+        let warn = Threshold::effective(self.warn_min_free_bytes, self.warn_min_free_percent);
+        let kill = Threshold::effective(self.kill_min_free_bytes, self.kill_min_free_percent);
+        let rearm = Threshold::effective(self.rearm_min_free_bytes, self.rearm_min_free_percent);
+        for (kind, healthier) in [("warn", warn), ("rearm", rearm)] {
+            if let (Some(healthier), Some(kill)) = (healthier, kill)
+                && !healthier.above(&kill, total.map(|(total, _)| total))
+            {
+                errors.push(ConfigError::ThresholdOrder(
+                    field(&healthier.field(kind)),
+                    healthier.to_string(),
+                    field(&kill.field("kill")),
+                    kill.to_string(),
+                ));
+            }
+        }
+
+        // A longer horizon is the less severe one
+        if let (Some(warn), Some(kill)) = (
+            self.warn_when_exhausted_within_ms,
+            self.kill_when_exhausted_within_ms,
+        ) && warn <= kill
+        {
+            errors.push(ConfigError::ThresholdOrder(
+                field("warnWhenExhaustedWithinMs"),
+                format!("{}ms", warn),
+                field("killWhenExhaustedWithinMs"),
+                format!("{}ms", kill),
+            ));
+        }
+
+        if errors.is_empty() {
+            Ok(())
         } else {
-            None
+            Err(errors)
+        }
+    }
+}
+
+/// A min-free limit as it is evaluated by the monitor.
+#[derive(Clone, Copy)]
+enum Threshold {
+    Bytes(u64),
+    Percent(f32),
+}
+
+impl Threshold {
+    fn effective(bytes: Option<u64>, percent: Option<f32>) -> Option<Self> {
+        bytes
+            .map(Threshold::Bytes)
+            .or(percent.map(Threshold::Percent))
+    }
+
+    // Mixed units can only be compared when the total is known; if it isn't, assume the best
+    fn above(&self, other: &Threshold, total: Option<u64>) -> bool {
+        match (self, other) {
+            (Threshold::Percent(a), Threshold::Percent(b)) => a > b,
+            (Threshold::Bytes(a), Threshold::Bytes(b)) => a > b,
+            (a, b) => total.is_none_or(|t| a.bytes(t) > b.bytes(t)),
+        }
+    }
+
+    fn bytes(&self, total: u64) -> u64 {
+        match self {
+            Threshold::Bytes(b) => *b,
+            Threshold::Percent(p) => (total as f64 * (*p as f64 / 100.0)) as u64,
+        }
+    }
+
+    fn field(&self, kind: &str) -> String {
+        match self {
+            Threshold::Bytes(_) => format!("{}MinFreeBytes", kind),
+            Threshold::Percent(_) => format!("{}MinFreePercent", kind),
+        }
+    }
+}
+
+impl fmt::Display for Threshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Threshold::Bytes(b) => write!(f, "{} bytes", b),
+            Threshold::Percent(p) => write!(f, "{}%", p),
+        }
+    }
+}
+
+/// PSI counterpart of the memory checks: pressure must reach the warn level before the kill
+/// level, and drop below the re-arm level before the next kill.
+pub fn check_psi_thresholds(
+    parsed: &psi::PsiConfigParsed,
+    section: &str,
+) -> Result<(), ConfigError> {
+    for (name, healthier) in [
+        ("warnMaxPercent", parsed.warn_max_percent),
+        ("rearmMaxPercent", parsed.rearm_max_percent),
+    ] {
+        if let (Some(healthier), Some(kill)) = (healthier, parsed.kill_max_percent)
+            && healthier >= kill
+        {
+            return Err(ConfigError::ThresholdOrder(
+                format!("{}.{}", section, name),
+                format!("{}%", healthier),
+                format!("{}.killMaxPercent", section),
+                format!("{}%", kill),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Upper limits on something that grows (a /proc/meminfo field, the compressed swap pool).
+/// Percentages are of total RAM; as with `MemoryConfig`, a byte limit overrides the percentage.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct UsageConfig {
+    pub warn_max_bytes: Option<String>,
+    pub warn_max_percent: Option<f32>,
+    pub kill_max_bytes: Option<String>,
+    pub kill_max_percent: Option<f32>,
+    /// Hysteresis as in `MemoryConfig`: the breach must hold this long...
+    pub kill_after_samples: Option<u32>,
+    pub kill_after_ms: Option<u64>,
+    /// ...and after a kill, the trigger stays disarmed until usage is back below this.
+    pub rearm_max_bytes: Option<String>,
+    pub rearm_max_percent: Option<f32>,
+}
+
+impl UsageConfig {
+    pub fn is_effectively_empty(&self) -> bool {
+        self.warn_max_bytes.is_none()
+            && self.warn_max_percent.is_none()
+            && self.kill_max_bytes.is_none()
+            && self.kill_max_percent.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UsageConfigParsed {
+    pub warn_max_bytes: Option<u64>,
+    pub warn_max_percent: Option<f32>,
+    pub kill_max_bytes: Option<u64>,
+    pub kill_max_percent: Option<f32>,
+    pub hold: KillHold,
+    pub rearm_max_bytes: Option<u64>,
+    pub rearm_max_percent: Option<f32>,
+}
+
+impl UsageConfigParsed {
+    /// `section` prefixes field names in errors (e.g. "meminfo.shmem"), `total` is total RAM.
+    pub fn try_from_config(
+        config: UsageConfig,
+        section: &str,
+        total: u64,
+    ) -> Result<Self, Vec<ConfigError>> {
+        let name = |setting: &str| format!("{}.{}", section, setting);
+        let mut errors = Vec::new();
+        let mut size = |setting: &str, value: &Option<String>| {
+            let s = value.as_ref()?;
+            let parsed = parse_size(s);
+            if parsed.is_none() {
+                errors.push(ConfigError::InvalidSize(name(setting), s.clone()));
+            }
+            parsed
         };
+        let warn_max_bytes = size("warnMaxBytes", &config.warn_max_bytes);
+        let kill_max_bytes = size("killMaxBytes", &config.kill_max_bytes);
+        let rearm_max_bytes = size("rearmMaxBytes", &config.rearm_max_bytes);
 
-        if let Some(p) = config.warn_min_free_percent {
-            if !(0.0..=100.0).contains(&p) {
-                return Err(ConfigError::InvalidPercent(
-                    "warnMinFreePercent".to_string(),
-                    p,
+        for (setting, percent) in [
+            ("warnMaxPercent", config.warn_max_percent),
+            ("killMaxPercent", config.kill_max_percent),
+            ("rearmMaxPercent", config.rearm_max_percent),
+        ] {
+            if let Some(p) = percent
+                && !(0.0..=100.0).contains(&p)
+            {
+                errors.push(ConfigError::InvalidPercent(name(setting), p));
+            }
+        }
+        for (setting, limit) in [
+            ("warnMaxBytes", warn_max_bytes),
+            ("killMaxBytes", kill_max_bytes),
+        ] {
+            if let Some(limit) = limit
+                && limit >= total
+            {
+                errors.push(ConfigError::ThresholdUnreachable(
+                    name(setting),
+                    limit,
+                    total,
+                    LimitPool::Memory,
+                    LimitKind::MaxUsage,
                 ));
             }
         }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
-        if let Some(p) = config.kill_min_free_percent {
-            if !(0.0..=100.0).contains(&p) {
-                return Err(ConfigError::InvalidPercent(
-                    "killMinFreePercent".to_string(),
-                    p,
+        let parsed = Self {
+            warn_max_bytes,
+            warn_max_percent: config.warn_max_percent,
+            kill_max_bytes,
+            kill_max_percent: config.kill_max_percent,
+            hold: KillHold::new(config.kill_after_samples, config.kill_after_ms),
+            rearm_max_bytes,
+            rearm_max_percent: config.rearm_max_percent,
+        };
+        // These limits are breached upwards: warn and rearm must be the lower ones
+        let show = |kind: &str, unit: &str, bytes: u64, percent: Option<f32>| match unit {
+            "bytes" => (
+                name(&format!("{}MaxBytes", kind)),
+                format!("{} bytes", bytes),
+            ),
+            _ => (
+                name(&format!("{}MaxPercent", kind)),
+                format!("{}%", percent.unwrap_or_default()),
+            ),
+        };
+        let mut errors = Vec::new();
+        for (kind, lower, percent) in [
+            ("warn", parsed.warn_limit(total), parsed.warn_max_percent),
+            ("rearm", parsed.rearm_limit(total), parsed.rearm_max_percent),
+        ] {
+            if let (Some((lower, lower_unit)), Some((kill, kill_unit))) =
+                (lower, parsed.kill_limit(total))
+                && lower >= kill
+            {
+                let (lower_setting, lower_str) = show(kind, lower_unit, lower, percent);
+                let (kill_setting, kill_str) =
+                    show("kill", kill_unit, kill, parsed.kill_max_percent);
+                errors.push(ConfigError::ThresholdOrder(
+                    lower_setting,
+                    lower_str,
+                    kill_setting,
+                    kill_str,
                 ));
             }
         }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(parsed)
+    }
 
-        Ok(Self {
-            warn_min_free_bytes,
-            warn_min_free_percent: config.warn_min_free_percent,
-            kill_min_free_bytes,
-            kill_min_free_percent: config.kill_min_free_percent,
-        })
+    /// Effective warn limit in bytes and whether it came from "bytes" or "percent".
+    pub fn warn_limit(&self, total: u64) -> Option<(u64, &'static str)> {
+        effective_usage_limit(self.warn_max_bytes, self.warn_max_percent, total)
+    }
+
+    pub fn kill_limit(&self, total: u64) -> Option<(u64, &'static str)> {
+        effective_usage_limit(self.kill_max_bytes, self.kill_max_percent, total)
+    }
+
+    pub fn rearm_limit(&self, total: u64) -> Option<(u64, &'static str)> {
+        effective_usage_limit(self.rearm_max_bytes, self.rearm_max_percent, total)
+    }
+
+    /// Whether a trigger that fired may fire again (always, without a re-arm limit).
+    pub fn rearmed(&self, value: u64, total: u64) -> bool {
+        self.rearm_limit(total)
+            .is_none_or(|(rearm, _)| value < rearm)
+    }
+}
+
+// Strict Priority: bytes win over percent
+fn effective_usage_limit(
+    bytes: Option<u64>,
+    percent: Option<f32>,
+    total: u64,
+) -> Option<(u64, &'static str)> {
+    if let Some(bytes) = bytes {
+        return Some((bytes, "bytes"));
     }
+    percent.map(|p| ((total as f64 * (p as f64 / 100.0)) as u64, "percent"))
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
@@ -101,14 +492,203 @@ impl MemoryConfigParsed {
 pub enum KillStrategy {
     LargestRss,
     HighestOomScore,
+    /// Sum of weighted factors (`killWeights`); the killTargets index is one of the factors.
+    Weighted,
+}
+
+/// Which figure stands for "the memory a process holds" (`killAccounting`).
+/// RSS counts shared pages (shmem, shared libraries, a browser's shared heaps) in full for every
+/// process mapping them, so killing one of many sharers frees far less than its RSS.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum KillAccounting {
+    /// Resident set from statm: cheapest, overstates shared memory.
+    #[default]
+    Rss,
+    /// Proportional set from smaps_rollup: each shared page split between its sharers.
+    Pss,
+    /// Unique set (Private_Clean + Private_Dirty): only what the process alone holds.
+    Uss,
+}
+
+impl KillAccounting {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KillAccounting::Rss => "rss",
+            KillAccounting::Pss => "pss",
+            KillAccounting::Uss => "uss",
+        }
+    }
+}
+
+/// Weights of the `weighted` strategy. Every factor is scaled to 0-1000 points (like oom_score)
+/// before weighting, so weights compare directly; negative weights favour the opposite end.
+/// Factors left out weigh 0.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields, default)]
+pub struct KillWeights {
+    pub rss: f64,          // Resident set, share of total RAM
+    pub pss: f64,          // Proportional set (smaps_rollup), share of total RAM
+    pub swap: f64,         // VmSwap, share of total RAM
+    pub oom_score: f64,    // /proc/<pid>/oom_score as is
+    pub age: f64,          // Process age, share of system uptime
+    pub cpu_idle: f64,     // Share of its lifetime the process spent off the CPU
+    pub target_index: f64, // killTargets entry i of N: (N - i) / N, no match: 0
+}
+
+impl KillWeights {
+    /// Used when `killWeights` is not set: the largest process wins, killTargets matches
+    /// get a boost on top.
+    pub fn recommended() -> Self {
+        Self {
+            rss: 1.0,
+            target_index: 1.0,
+            ..Default::default()
+        }
+    }
+
+    fn as_array(&self) -> [(&'static str, f64); 7] {
+        [
+            ("rss", self.rss),
+            ("pss", self.pss),
+            ("swap", self.swap),
+            ("oomScore", self.oom_score),
+            ("age", self.age),
+            ("cpuIdle", self.cpu_idle),
+            ("targetIndex", self.target_index),
+        ]
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let weights = self.as_array();
+        if let Some((name, w)) = weights.iter().find(|(_, w)| !w.is_finite()) {
+            return Err(ConfigError::KillWeights(format!(
+                "{} must be a finite number, got {}",
+                name, w
+            )));
+        }
+        if weights.iter().all(|(_, w)| *w == 0.0) {
+            return Err(ConfigError::KillWeights(
+                "all weights are 0, so every process would score the same".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// An `ignoreNames` entry: a bare cmdline pattern or a matcher object.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum IgnoreNameConfig {
+    Pattern(String),
+    Detailed(MatcherConfig),
+}
+
+impl IgnoreNameConfig {
+    pub fn matcher(&self) -> MatcherConfig {
+        match self {
+            IgnoreNameConfig::Pattern(p) => MatcherConfig::from_pattern(p),
+            IgnoreNameConfig::Detailed(m) => m.clone(),
+        }
+    }
+}
+
+/// A `killTargets` entry: either a bare pattern string or an object with per-target options.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(untagged)]
+pub enum KillTargetConfig {
+    Pattern(String),
+    Detailed(KillTargetOptions),
+}
+
+/// The matcher fields repeat `MatcherConfig`'s: serde can't deny unknown fields
+/// across a flattened struct.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct KillTargetOptions {
+    #[serde(alias = "pattern")]
+    pub cmdline: Option<String>,
+    pub comm: Option<String>,
+    pub exe: Option<String>,
+    pub user: Option<String>,
+    pub cgroup: Option<String>,
+    pub parent: Option<String>,
+    pub ancestor: Option<String>,
+    pub ancestor_depth: Option<u32>,
+    /// Kill the champion's whole cgroup via `cgroup.kill` instead of the single PID.
+    #[serde(default)]
+    pub kill_cgroup: bool,
+    #[serde(default)]
+    pub action: TargetAction,
+}
+
+/// What happens to a selected champion. Suspended champions are resumed once memory recovers.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum TargetAction {
+    #[default]
+    Kill,
+    Freeze, // cgroup v2 cgroup.freeze on the champion's cgroup
+    Stop,   // SIGSTOP on the champion
+}
+
+impl TargetAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TargetAction::Kill => "kill",
+            TargetAction::Freeze => "freeze",
+            TargetAction::Stop => "stop",
+        }
+    }
+}
+
+impl KillTargetConfig {
+    pub fn matcher(&self) -> MatcherConfig {
+        match self {
+            KillTargetConfig::Pattern(p) => MatcherConfig::from_pattern(p),
+            KillTargetConfig::Detailed(o) => MatcherConfig {
+                cmdline: o.cmdline.clone(),
+                comm: o.comm.clone(),
+                exe: o.exe.clone(),
+                user: o.user.clone(),
+                cgroup: o.cgroup.clone(),
+                parent: o.parent.clone(),
+                ancestor: o.ancestor.clone(),
+                ancestor_depth: o.ancestor_depth,
+            },
+        }
+    }
+
+    pub fn kill_cgroup(&self) -> bool {
+        match self {
+            KillTargetConfig::Pattern(_) => false,
+            KillTargetConfig::Detailed(o) => o.kill_cgroup,
+        }
+    }
+
+    pub fn action(&self) -> TargetAction {
+        match self {
+            KillTargetConfig::Pattern(_) => TargetAction::Kill,
+            KillTargetConfig::Detailed(o) => o.action,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KillTarget {
+    pub matcher: Matcher,
+    pub kill_cgroup: bool,
+    pub action: TargetAction,
 }
 
 impl MemoryConfig {
-    fn is_effectively_empty(&self) -> bool {
+    pub fn is_effectively_empty(&self) -> bool {
         self.warn_min_free_bytes.is_none()
             && self.warn_min_free_percent.is_none()
             && self.kill_min_free_bytes.is_none()
             && self.kill_min_free_percent.is_none()
+            && self.warn_when_exhausted_within_ms.is_none()
+            && self.kill_when_exhausted_within_ms.is_none()
     }
 }
 
@@ -122,11 +702,20 @@ fn warn_interval() -> u64 {
 fn sigterm_wait_ms() -> u64 {
     5000
 }
+fn thaw_dwell_ms() -> u64 {
+    30000
+}
+fn pause_grace_ms() -> u64 {
+    10000
+}
 fn default_strategy() -> KillStrategy {
     KillStrategy::HighestOomScore
 }
-fn default_kill_targets() -> Vec<String> {
-    vec!["type=renderer".to_string(), "-contentproc".to_string()]
+fn default_kill_targets() -> Vec<KillTargetConfig> {
+    vec![
+        KillTargetConfig::Pattern("type=renderer".to_string()),
+        KillTargetConfig::Pattern("-contentproc".to_string()),
+    ]
 }
 
 #[derive(Debug)]
@@ -134,15 +723,28 @@ pub struct RuntimeContext {
     pub psi: Option<psi::PsiConfigParsed>,
     pub ram: Option<MemoryConfigParsed>,
     pub swap: Option<MemoryConfigParsed>,
+    pub combined: Option<MemoryConfigParsed>,
+    pub compressed_swap: Option<UsageConfigParsed>,
+    pub meminfo: Vec<MemInfoLimit>,
+    pub cgroups: Vec<CgroupConfigParsed>,
 
     pub check_interval_ms: u64,
     pub warn_reset_ms: u64,
     pub sigterm_wait_ms: u64,
+    pub thaw_dwell_ms: u64,
+    pub pause_grace_ms: u64,
 
     pub kill_strategy: KillStrategy,
+    pub kill_weights: KillWeights, // Only used by KillStrategy::Weighted
+    pub kill_accounting: KillAccounting,
+
+    pub ignore_names: Vec<Matcher>,
+    pub kill_targets: Vec<KillTarget>,
+    pub kill_scope: Vec<ScopePattern>, // Empty = no restriction
 
-    pub ignore_names_regex: Vec<Pattern>,
-    pub kill_targets_regex: Vec<Pattern>,
+    // Provenance, kept for hot-reload (file watching and change reporting)
+    pub source: Config,
+    pub source_path: Option<PathBuf>,
 }
 
 #[derive(Debug)]
@@ -150,92 +752,235 @@ pub enum Pattern {
     Literal(String),
     Regex(Regex),
     StartsWith(String),
+    Glob(String), // Whole-string match, only built by `parse_glob`
 }
 
 impl Pattern {
+    pub fn parse(s: &str) -> Result<Self, regex::Error> {
+        if s.starts_with('/') && s.ends_with('/') && s.len() > 2 {
+            // Case 1: Regex
+            Ok(Pattern::Regex(Regex::new(&s[1..s.len() - 1])?))
+        } else if s.starts_with('^') && s.len() > 1 {
+            // Case 2: StartsWith
+            Ok(Pattern::StartsWith(s[1..].to_string()))
+        } else {
+            // Case 3: Literal
+            Ok(Pattern::Literal(s.to_string()))
+        }
+    }
+
+    /// For paths: like `parse`, but a pattern with '*' or '?' (and not a /regex/) is a glob.
+    pub fn parse_glob(s: &str) -> Result<Self, regex::Error> {
+        let is_regex = s.starts_with('/') && s.ends_with('/') && s.len() > 2;
+        if !is_regex && s.contains(['*', '?']) {
+            return Ok(Pattern::Glob(s.to_string()));
+        }
+        Self::parse(s)
+    }
+
     pub fn matches(&self, s: &str) -> bool {
         match self {
             Pattern::Literal(lit) => s.contains(lit),
             Pattern::Regex(re) => re.is_match(s),
             Pattern::StartsWith(prefix) => s.starts_with(prefix),
+            Pattern::Glob(glob) => glob_match(glob.as_bytes(), s.as_bytes()),
         }
     }
 }
 
 impl Config {
     pub fn load(cli_config_path: Option<PathBuf>) -> Result<RuntimeContext, ConfigError> {
-        let mut config = match cli_config_path {
+        let (config, source_path) = Self::read(cli_config_path)?;
+        config
+            .into_runtime(source_path)
+            .map_err(|mut errors| errors.remove(0))
+    }
+
+    /// Locates and deserializes the config file (or falls back to sane defaults).
+    pub fn read(
+        cli_config_path: Option<PathBuf>,
+    ) -> Result<(Config, Option<PathBuf>), ConfigError> {
+        match cli_config_path {
             Some(path) => {
                 if !path.exists() {
                     // Was Exit code 2
                     return Err(ConfigError::ConfigFileNotFound(path));
                 }
-                Self::parse_file(&path)?
+                Ok((Self::parse_file(&path)?, Some(path)))
             }
-            None => Self::find_and_load_config()?,
-        };
+            None => Self::find_and_load_config(),
+        }
+    }
 
-        config.validate()?;
+    /// Validates and compiles the config. Keeps going after a failure so that every
+    /// problem is reported at once; errors are in the order the sections are checked.
+    pub fn into_runtime(
+        mut self,
+        source_path: Option<PathBuf>,
+    ) -> Result<RuntimeContext, Vec<ConfigError>> {
+        let mut errors = Vec::new();
+        self.validate(&mut errors);
+        let config = self;
+        let source = config.clone();
 
         // Optimization: Compile Regex patterns
-        let ignore_names_regex = compile_patterns(&config.ignore_names, "ignore_names")?;
-        let kill_targets_regex = compile_patterns(&config.kill_targets, "kill_targets")?;
+        let ignore_names = compile_matchers(
+            config.ignore_names.iter().map(IgnoreNameConfig::matcher),
+            "ignore_names",
+            &mut errors,
+        );
+        let kill_targets = compile_matchers(
+            config.kill_targets.iter().map(KillTargetConfig::matcher),
+            "kill_targets",
+            &mut errors,
+        )
+        .into_iter()
+        .zip(&config.kill_targets)
+        .map(|(matcher, t)| KillTarget {
+            matcher,
+            kill_cgroup: t.kill_cgroup(),
+            action: t.action(),
+        })
+        .collect();
+        let mut kill_scope = Vec::with_capacity(config.kill_scope.len());
+        for raw in &config.kill_scope {
+            match ScopePattern::parse(raw) {
+                Some(scope) => kill_scope.push(scope),
+                None => errors.push(ConfigError::Cgroup(
+                    raw.clone(),
+                    "empty killScope entry".to_string(),
+                )),
+            }
+        }
 
-        let psi_parsed = if let Some(p) = config.psi {
-            let parsed = psi::PsiConfigParsed::try_from_config(p, config.check_interval_ms)
-                .map_err(|e| ConfigError::PsiConfig(e.to_string()))?;
+        let kill_weights = config.kill_weights.unwrap_or_else(KillWeights::recommended);
+        if config.kill_strategy == KillStrategy::Weighted
+            && let Err(e) = kill_weights.validate()
+        {
+            errors.push(e);
+        }
 
-            if let Err(e) = psi::validate_psi_availability() {
-                return Err(ConfigError::PsiUnavailable(e.to_string()));
+        let psi_parsed = config.psi.and_then(|p| {
+            let parsed = psi::PsiConfigParsed::try_from_config(p, config.check_interval_ms)
+                .map_err(|e| errors.push(ConfigError::PsiConfig(e.to_string())))
+                .ok()?;
+            if let Err(e) = check_psi_thresholds(&parsed, "psi") {
+                errors.push(e);
+                return None;
+            }
+            if let Err(e) = psi::validate_psi_availability(&parsed, Path::new(psi::PSI_MEMORY_PATH))
+            {
+                errors.push(ConfigError::PsiUnavailable(e.to_string()));
+                return None;
             }
             Some(parsed)
-        } else {
-            None
-        };
+        });
 
-        let ram_parsed = if let Some(r) = config.ram {
-            Some(MemoryConfigParsed::try_from_config(r)?)
-        } else {
-            None
-        };
+        let ram_parsed = config.ram.and_then(|r| {
+            MemoryConfigParsed::try_from_config(
+                r,
+                "ram",
+                Some((get_total_memory(), LimitPool::Memory)),
+            )
+            .map_err(|e| errors.extend(e))
+            .ok()
+        });
 
-        let swap_parsed = if let Some(s) = config.swap {
-            Some(MemoryConfigParsed::try_from_config(s)?)
-        } else {
-            None
-        };
+        let swap_parsed = config.swap.and_then(|s| {
+            // Without swap the section is inert (the monitor skips it), so nothing is unreachable.
+            // zram swap counts for the RAM it can save, as in the monitor.
+            let total = Some(effective_total_swap()).filter(|&total| total > 0);
+            MemoryConfigParsed::try_from_config(s, "swap", total.map(|t| (t, LimitPool::Swap)))
+                .map_err(|e| errors.extend(e))
+                .ok()
+        });
+
+        let combined_parsed = config.combined.and_then(|c| {
+            let total = get_total_memory() + effective_total_swap();
+            MemoryConfigParsed::try_from_config(c, "combined", Some((total, LimitPool::Combined)))
+                .map_err(|e| errors.extend(e))
+                .ok()
+        });
+
+        let compressed_swap_parsed = config.compressed_swap.and_then(|c| {
+            UsageConfigParsed::try_from_config(c, "compressedSwap", get_total_memory())
+                .map_err(|e| errors.extend(e))
+                .ok()
+        });
+
+        let mut meminfo_parsed = Vec::with_capacity(config.meminfo.len());
+        for (field, limit) in config.meminfo {
+            let section = format!("meminfo.{}", field.config_key());
+            match UsageConfigParsed::try_from_config(limit, &section, get_total_memory()) {
+                Ok(limit) => meminfo_parsed.push(MemInfoLimit { field, limit }),
+                Err(e) => errors.extend(e),
+            }
+        }
+
+        let mut cgroups_parsed = Vec::with_capacity(config.cgroups.len());
+        for (i, c) in config.cgroups.into_iter().enumerate() {
+            match CgroupConfigParsed::try_from_config(c, i, config.check_interval_ms) {
+                Ok(parsed) => cgroups_parsed.push(parsed),
+                Err(e) => errors.extend(e),
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
 
         Ok(RuntimeContext {
             psi: psi_parsed,
             ram: ram_parsed,
             swap: swap_parsed,
+            combined: combined_parsed,
+            compressed_swap: compressed_swap_parsed,
+            meminfo: meminfo_parsed,
+            cgroups: cgroups_parsed,
             check_interval_ms: config.check_interval_ms,
             warn_reset_ms: config.warn_reset_ms,
             sigterm_wait_ms: config.sigterm_wait_ms,
+            thaw_dwell_ms: config.thaw_dwell_ms,
+            pause_grace_ms: config.pause_grace_ms,
             kill_strategy: config.kill_strategy,
-            ignore_names_regex,
-            kill_targets_regex,
+            kill_weights,
+            kill_accounting: config.kill_accounting,
+            ignore_names,
+            kill_targets,
+            kill_scope,
+            source,
+            source_path,
         })
     }
 
-    fn find_and_load_config() -> Result<Config, ConfigError> {
-        if let Some(config_home) =
-            directories::BaseDirs::new().map(|b| b.config_dir().to_path_buf())
-        {
-            let extensions = ["yaml", "yml", "json", "toml"];
-            for ext in &extensions {
-                let path = config_home.join(format!("ram-sentinel.{}", ext));
-                if path.exists() {
-                    return Self::parse_file(&path);
-                }
-            }
+    /// Lists the (camelCase, dot-separated) fields whose values differ between two configs.
+    pub fn changed_fields(&self, newer: &Config) -> Vec<String> {
+        let old = serde_json::to_value(self).unwrap_or(Value::Null);
+        let new = serde_json::to_value(newer).unwrap_or(Value::Null);
+        let mut changed = Vec::new();
+        diff_values("", &old, &new, &mut changed);
+        changed
+    }
+
+    fn find_and_load_config() -> Result<(Config, Option<PathBuf>), ConfigError> {
+        if let Some(path) = Self::discover() {
+            return Ok((Self::parse_file(&path)?, Some(path)));
         }
 
         logging::emit(&SentinelEvent::Message {
             level: LogLevel::Info,
             text: "No configuration file found. Loading sane defaults.".to_string(),
         });
-        Ok(Self::sane_defaults())
+        Ok((Self::sane_defaults(), None))
+    }
+
+    /// The first ram-sentinel.{yaml,yml,json,toml} in the user's config directory.
+    pub fn discover() -> Option<PathBuf> {
+        let config_home = directories::BaseDirs::new()?.config_dir().to_path_buf();
+        ["yaml", "yml", "json", "toml"]
+            .iter()
+            .map(|ext| config_home.join(format!("ram-sentinel.{}", ext)))
+            .find(|path| path.exists())
     }
 
     fn parse_file(path: &Path) -> Result<Config, ConfigError> {
@@ -244,60 +989,77 @@ impl Config {
 
         let ext = path.extension().and_then(|s| s.to_str()).unwrap_or("yaml");
 
-        macro_rules! parse {
-            ($func:expr) => {
-                $func(&content)
-                    .map_err(|e| ConfigError::FileParse(path.to_path_buf(), e.to_string()))
-            };
-        }
+        let parse_error = |message: String, location: Option<(usize, usize)>| {
+            ConfigError::FileParse(path.to_path_buf(), message, location)
+        };
 
         match ext {
-            "yaml" | "yml" => parse!(serde_yaml::from_str),
-            "json" => parse!(serde_json::from_str),
-            "toml" => parse!(toml::from_str),
-            _ => parse!(serde_yaml::from_str),
+            "json" => serde_json::from_str(&content)
+                .map_err(|e| parse_error(e.to_string(), Some((e.line(), e.column())))),
+            "toml" => toml::from_str(&content).map_err(|e| {
+                let location = e.span().map(|span| line_column(&content, span.start));
+                parse_error(e.to_string(), location)
+            }),
+            // "yaml", "yml" and anything else
+            _ => serde_yaml::from_str(&content).map_err(|e| {
+                let location = e.location().map(|l| (l.line(), l.column()));
+                parse_error(e.to_string(), location)
+            }),
         }
     }
 
     pub fn sane_defaults() -> Config {
         Config {
-            psi: Some(psi::PsiConfig {
-                warn_max_percent: None,
-                kill_max_percent: None,
-                amount_to_free: None,
-                check_interval_ms: None,
-            }),
+            psi: Some(psi::PsiConfig::default()),
             ram: Some(MemoryConfig {
-                warn_min_free_bytes: None,
                 warn_min_free_percent: Some(10.0),
-                kill_min_free_bytes: None,
                 kill_min_free_percent: Some(5.0),
+                ..Default::default()
             }),
-            swap: Some(MemoryConfig {
-                warn_min_free_bytes: None,
-                warn_min_free_percent: None,
-                kill_min_free_bytes: None,
-                kill_min_free_percent: None,
-            }),
+            swap: Some(MemoryConfig::default()),
+            combined: None,
+            compressed_swap: None,
+            meminfo: BTreeMap::new(),
+            cgroups: vec![],
             check_interval_ms: default_interval(),
             warn_reset_ms: warn_interval(),
             sigterm_wait_ms: sigterm_wait_ms(),
+            thaw_dwell_ms: thaw_dwell_ms(),
+            pause_grace_ms: pause_grace_ms(),
             ignore_names: vec![],
             kill_targets: default_kill_targets(),
+            kill_scope: vec![],
             kill_strategy: default_strategy(),
+            kill_weights: None,
+            kill_accounting: KillAccounting::default(),
         }
     }
 
-    fn validate(&mut self) -> Result<(), ConfigError> {
-        let psi_empty = self.psi.as_ref().map_or(true, |p| p.is_effectively_empty());
-        let ram_empty = self.ram.as_ref().map_or(true, |r| r.is_effectively_empty());
-        let swap_empty = self
-            .swap
+    fn validate(&mut self, errors: &mut Vec<ConfigError>) {
+        let psi_empty = self.psi.as_ref().is_none_or(|p| p.is_effectively_empty());
+        let ram_empty = self.ram.as_ref().is_none_or(|r| r.is_effectively_empty());
+        let swap_empty = self.swap.as_ref().is_none_or(|s| s.is_effectively_empty());
+        let combined_empty = self
+            .combined
+            .as_ref()
+            .is_none_or(|c| c.is_effectively_empty());
+        let compressed_swap_empty = self
+            .compressed_swap
             .as_ref()
-            .map_or(true, |s| s.is_effectively_empty());
+            .is_none_or(|c| c.is_effectively_empty());
 
-        if psi_empty && ram_empty && swap_empty {
-            return Err(ConfigError::EffectiveEmpty);
+        self.meminfo.retain(|_, l| !l.is_effectively_empty());
+        self.cgroups.retain(|c| !c.is_effectively_empty());
+
+        if psi_empty
+            && ram_empty
+            && swap_empty
+            && combined_empty
+            && compressed_swap_empty
+            && self.meminfo.is_empty()
+            && self.cgroups.is_empty()
+        {
+            errors.push(ConfigError::EffectiveEmpty);
         }
         if psi_empty {
             self.psi = None;
@@ -308,43 +1070,315 @@ impl Config {
         if swap_empty {
             self.swap = None;
         }
+        if combined_empty {
+            self.combined = None;
+        }
+        if compressed_swap_empty {
+            self.compressed_swap = None;
+        }
 
         if self.check_interval_ms > 300000 {
-            return Err(ConfigError::IntervalTooHigh(self.check_interval_ms));
+            errors.push(ConfigError::IntervalTooHigh(self.check_interval_ms));
         }
 
         if self.check_interval_ms < 100 {
-            return Err(ConfigError::IntervalTooLow(self.check_interval_ms));
+            errors.push(ConfigError::IntervalTooLow(self.check_interval_ms));
         }
-
-        Ok(())
     }
 }
 
-fn compile_patterns(raw: &[String], field_name: &str) -> Result<Vec<Pattern>, ConfigError> {
-    let mut patterns = Vec::new();
-    for (i, s) in raw.iter().enumerate() {
-        if s.starts_with('/') && s.ends_with('/') && s.len() > 2 {
-            // Case 1: Regex
-            let regex_str = &s[1..s.len() - 1];
-            match Regex::new(regex_str) {
-                Ok(re) => patterns.push(Pattern::Regex(re)),
-                Err(e) => {
-                    return Err(ConfigError::RegexError(
-                        field_name.to_string(),
-                        i,
-                        s.clone(),
-                        e.to_string(),
-                    ));
-                }
+fn diff_values(prefix: &str, old: &Value, new: &Value, changed: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                let a_val = a.get(key).unwrap_or(&Value::Null);
+                let b_val = b.get(key).unwrap_or(&Value::Null);
+                diff_values(&path, a_val, b_val, changed);
+            }
+        }
+        _ => {
+            if old != new {
+                changed.push(prefix.to_string());
             }
-        } else if s.starts_with('^') && s.len() > 1 {
-            // Case 2: StartsWith
-            patterns.push(Pattern::StartsWith(s[1..].to_string()));
-        } else {
-            // Case 3: Literal
-            patterns.push(Pattern::Literal(s.clone()));
         }
     }
-    Ok(patterns)
+}
+
+// Invalid entries are reported to `errors` and left out of the result
+fn compile_matchers(
+    configs: impl Iterator<Item = MatcherConfig>,
+    field_name: &str,
+    errors: &mut Vec<ConfigError>,
+) -> Vec<Matcher> {
+    let mut matchers = Vec::new();
+    for (i, config) in configs.enumerate() {
+        match Matcher::compile(&config, field_name, i) {
+            Ok(matcher) => matchers.push(matcher),
+            Err(e) => errors.push(e),
+        }
+    }
+    matchers
+}
+
+/// 1-based (line, column) of a byte offset in `content`.
+pub fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GB: u64 = 1_000_000_000;
+
+    // Each error as "<kind> <field>", to compare without depending on message wording
+    fn problems(config: MemoryConfig, total: Option<u64>) -> Vec<String> {
+        let total = total.map(|t| (t, LimitPool::Memory));
+        let Err(errors) = MemoryConfigParsed::try_from_config(config, "ram", total) else {
+            return Vec::new();
+        };
+        errors
+            .iter()
+            .map(|e| match e {
+                ConfigError::ThresholdOrder(healthier, _, kill, _) => {
+                    format!("order {} {}", healthier, kill)
+                }
+                ConfigError::ThresholdUnreachable(field, ..) => format!("unreachable {}", field),
+                ConfigError::InvalidSize(field, _) => format!("size {}", field),
+                ConfigError::InvalidPercent(field, _) => format!("percent {}", field),
+                other => other.to_string(),
+            })
+            .collect()
+    }
+
+    fn bytes(s: &str) -> Option<String> {
+        Some(s.to_string())
+    }
+
+    #[test]
+    fn warn_must_be_above_kill() {
+        let config = MemoryConfig {
+            warn_min_free_percent: Some(5.0),
+            kill_min_free_percent: Some(10.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            ["order ram.warnMinFreePercent ram.killMinFreePercent"]
+        );
+
+        let config = MemoryConfig {
+            warn_min_free_percent: Some(10.0),
+            kill_min_free_percent: Some(5.0),
+            ..Default::default()
+        };
+        assert!(problems(config, Some(8 * GB)).is_empty());
+    }
+
+    #[test]
+    fn bytes_override_percent_in_order_check() {
+        // The warn percentage is ignored: 100MB warn vs 500MB kill is inverted
+        let config = MemoryConfig {
+            warn_min_free_bytes: bytes("100MB"),
+            warn_min_free_percent: Some(50.0),
+            kill_min_free_bytes: bytes("500MB"),
+            ..Default::default()
+        };
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            ["order ram.warnMinFreeBytes ram.killMinFreeBytes"]
+        );
+    }
+
+    #[test]
+    fn mixed_units_compare_against_total() {
+        let config = || MemoryConfig {
+            warn_min_free_bytes: bytes("1GB"),
+            kill_min_free_percent: Some(5.0),
+            ..Default::default()
+        };
+        // 5% of 8GB = 400MB < 1GB; 5% of 40GB = 2GB > 1GB; unknown total gets the benefit of the doubt
+        assert!(problems(config(), Some(8 * GB)).is_empty());
+        assert_eq!(
+            problems(config(), Some(40 * GB)),
+            ["order ram.warnMinFreeBytes ram.killMinFreePercent"]
+        );
+        assert!(problems(config(), None).is_empty());
+    }
+
+    #[test]
+    fn rearm_must_be_above_kill() {
+        let config = MemoryConfig {
+            kill_min_free_percent: Some(5.0),
+            rearm_min_free_percent: Some(5.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            ["order ram.rearmMinFreePercent ram.killMinFreePercent"]
+        );
+    }
+
+    #[test]
+    fn byte_limits_must_be_below_total() {
+        let config = MemoryConfig {
+            warn_min_free_bytes: bytes("16GB"),
+            kill_min_free_bytes: bytes("8GB"),
+            ..Default::default()
+        };
+        // Order isn't checked against impossible limits
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            [
+                "unreachable ram.warnMinFreeBytes",
+                "unreachable ram.killMinFreeBytes"
+            ]
+        );
+    }
+
+    #[test]
+    fn every_invalid_field_is_reported() {
+        let config = MemoryConfig {
+            warn_min_free_bytes: bytes("lots"),
+            kill_min_free_percent: Some(120.0),
+            rearm_min_free_percent: Some(-1.0),
+            ..Default::default()
+        };
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            [
+                "size ram.warnMinFreeBytes",
+                "percent ram.killMinFreePercent",
+                "percent ram.rearmMinFreePercent"
+            ]
+        );
+    }
+
+    #[test]
+    fn longer_horizon_is_the_warning() {
+        let config = MemoryConfig {
+            warn_when_exhausted_within_ms: Some(10_000),
+            kill_when_exhausted_within_ms: Some(30_000),
+            ..Default::default()
+        };
+        assert_eq!(
+            problems(config, Some(8 * GB)),
+            ["order ram.warnWhenExhaustedWithinMs ram.killWhenExhaustedWithinMs"]
+        );
+    }
+
+    #[test]
+    fn unreachable_limits_name_their_pool() {
+        let config = MemoryConfig {
+            kill_min_free_bytes: Some("5G".to_string()),
+            ..Default::default()
+        };
+        let total = Some((4 * GB, LimitPool::Swap));
+        let Err(errors) = MemoryConfigParsed::try_from_config(config, "swap", total) else {
+            panic!("accepted a limit above total swap");
+        };
+        assert!(matches!(
+            errors[..],
+            [ConfigError::ThresholdUnreachable(
+                _,
+                _,
+                _,
+                LimitPool::Swap,
+                LimitKind::MinFree
+            )]
+        ));
+        assert!(errors[0].to_string().contains("effective total swap"));
+        assert!(errors[0].to_string().ends_with("it is always breached"));
+
+        let usage = UsageConfig {
+            kill_max_bytes: Some("20G".to_string()),
+            ..Default::default()
+        };
+        let Err(errors) = UsageConfigParsed::try_from_config(usage, "meminfo.shmem", 10 * GB)
+        else {
+            panic!("accepted a limit above total memory");
+        };
+        assert!(errors[0].to_string().contains("below total memory"));
+        assert!(errors[0].to_string().ends_with("it can never be reached"));
+    }
+
+    #[test]
+    fn usage_rearm_must_be_below_kill() {
+        let usage = |config: UsageConfig| match UsageConfigParsed::try_from_config(
+            config,
+            "meminfo.shmem",
+            10 * GB,
+        ) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.to_string()).collect(),
+        };
+        let rearm_above = usage(UsageConfig {
+            kill_max_percent: Some(30.0),
+            rearm_max_percent: Some(40.0),
+            ..Default::default()
+        });
+        assert_eq!(rearm_above.len(), 1);
+        assert!(rearm_above[0].contains("meminfo.shmem.rearmMaxPercent"));
+        let ok = usage(UsageConfig {
+            warn_max_percent: Some(20.0),
+            kill_max_bytes: Some("3G".to_string()),
+            rearm_max_bytes: Some("2G".to_string()),
+            ..Default::default()
+        });
+        assert!(ok.is_empty());
+    }
+
+    #[test]
+    fn pattern_kinds() {
+        assert!(matches!(Pattern::parse("/a.c/"), Ok(Pattern::Regex(_))));
+        assert!(matches!(Pattern::parse("^firefox"), Ok(Pattern::StartsWith(p)) if p == "firefox"));
+        assert!(matches!(Pattern::parse("npm"), Ok(Pattern::Literal(p)) if p == "npm"));
+        // Too short to be a regex or prefix
+        assert!(matches!(Pattern::parse("//"), Ok(Pattern::Literal(_))));
+        assert!(matches!(Pattern::parse("^"), Ok(Pattern::Literal(_))));
+        assert!(Pattern::parse("/(/").is_err());
+    }
+
+    #[test]
+    fn pattern_matching() {
+        assert!(
+            Pattern::parse("npm")
+                .unwrap()
+                .matches("node /usr/bin/npm start")
+        );
+        assert!(
+            Pattern::parse("^node")
+                .unwrap()
+                .matches("node /usr/bin/npm")
+        );
+        assert!(!Pattern::parse("^npm").unwrap().matches("node /usr/bin/npm"));
+        assert!(Pattern::parse("/^no.e /").unwrap().matches("node x"));
+    }
+
+    #[test]
+    fn globs_match_the_whole_string() {
+        let glob = Pattern::parse_glob("/usr/lib/*/chrome").unwrap();
+        assert!(matches!(glob, Pattern::Glob(_)));
+        assert!(glob.matches("/usr/lib/chromium/chrome"));
+        assert!(!glob.matches("/opt/usr/lib/chromium/chrome"));
+        assert!(!glob.matches("/usr/lib/chromium/chrome-sandbox"));
+        // Without wildcards, or as a regex, it parses as usual
+        assert!(matches!(
+            Pattern::parse_glob("/usr/bin/node"),
+            Ok(Pattern::Literal(_))
+        ));
+        assert!(matches!(
+            Pattern::parse_glob("/chrom.*/"),
+            Ok(Pattern::Regex(_))
+        ));
+    }
 }
@@ -5,7 +5,7 @@ use std::path::PathBuf;
 #[derive(Debug)]
 pub enum ConfigError {
     FileRead(PathBuf, io::Error),
-    FileParse(PathBuf, String),
+    FileParse(PathBuf, String, Option<(usize, usize)>), // path, message, (line, column)
     ConfigFileNotFound(PathBuf),
     EffectiveEmpty,
     IntervalTooHigh(u64),
@@ -15,9 +15,49 @@ pub enum ConfigError {
     RegexError(String, usize, String, String), // field_name, index, pattern, error
     InvalidSize(String, String),               // field_name, value
     InvalidPercent(String, f32),               // field_name, value
+    Cgroup(String, String),                    // cgroup path, reason
+    ThresholdOrder(String, String, String, String), // warn field, warn value, kill field, kill value
+    ThresholdUnreachable(String, u64, u64, LimitPool, LimitKind), // field_name, limit, total of its pool
+    KillWeights(String),
+    Matcher(String, usize, String, String), // field_name, index, entry, reason
+}
+
+/// The memory a byte limit is measured against.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitPool {
+    Memory,
+    Swap,
+    Combined,
+    CgroupMax,
+}
+
+impl LimitPool {
+    fn describe(self) -> &'static str {
+        match self {
+            LimitPool::Memory => "total memory",
+            LimitPool::Swap => "effective total swap",
+            LimitPool::Combined => "total memory + effective swap",
+            LimitPool::CgroupMax => "memory.max",
+        }
+    }
+}
+
+/// Whether a limit is a floor on free memory or a ceiling on usage.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitKind {
+    MinFree,
+    MaxUsage,
 }
 
 impl ConfigError {
+    /// (line, column) in the config file, when the parser reported one.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        match self {
+            ConfigError::FileParse(_, _, location) => *location,
+            _ => None,
+        }
+    }
+
     pub fn exit_code(&self) -> i32 {
         match self {
             ConfigError::FileRead(..) => 2,
@@ -31,6 +71,11 @@ impl ConfigError {
             ConfigError::RegexError(..) => 9,
             ConfigError::InvalidSize(..) => 10,
             ConfigError::InvalidPercent(..) => 11,
+            ConfigError::Cgroup(..) => 12,
+            ConfigError::ThresholdOrder(..) => 13,
+            ConfigError::ThresholdUnreachable(..) => 14,
+            ConfigError::KillWeights(_) => 15,
+            ConfigError::Matcher(..) => 16,
         }
     }
 }
@@ -41,7 +86,7 @@ impl fmt::Display for ConfigError {
             ConfigError::FileRead(path, e) => {
                 write!(f, "Error reading config file {:?}: {}", path, e)
             }
-            ConfigError::FileParse(path, e) => {
+            ConfigError::FileParse(path, e, _) => {
                 write!(f, "Error parsing config file {:?}: {}", path, e)
             }
             ConfigError::ConfigFileNotFound(path) => {
@@ -72,6 +117,35 @@ impl fmt::Display for ConfigError {
             ConfigError::InvalidPercent(field, val) => {
                 write!(f, "{} must be between 0-100, got {}", field, val)
             }
+            ConfigError::Cgroup(path, reason) => {
+                write!(f, "Invalid cgroup '{}': {}", path, reason)
+            }
+            ConfigError::ThresholdOrder(warn_field, warn, kill_field, kill) => write!(
+                f,
+                "{} ({}) must be less severe than {} ({})",
+                warn_field, warn, kill_field, kill
+            ),
+            ConfigError::KillWeights(e) => write!(f, "Invalid killWeights: {}", e),
+            ConfigError::Matcher(field, idx, entry, reason) => write!(
+                f,
+                "Invalid matcher in {}: entry {} ('{}'): {}",
+                field, idx, entry, reason
+            ),
+            ConfigError::ThresholdUnreachable(field, limit, total, pool, kind) => {
+                let consequence = match kind {
+                    LimitKind::MinFree => "it is always breached",
+                    LimitKind::MaxUsage => "it can never be reached",
+                };
+                write!(
+                    f,
+                    "{} ({} bytes) must be below {} ({} bytes), otherwise {}",
+                    field,
+                    limit,
+                    pool.describe(),
+                    total,
+                    consequence
+                )
+            }
         }
     }
 }
@@ -0,0 +1,440 @@
+use crate::config::RuntimeContext;
+use crate::events::{LogLevel, SentinelEvent};
+use crate::killer::Killer;
+use crate::logging;
+use crate::monitor::Monitor;
+use crate::utils::parse_duration;
+use clap::{Subcommand, ValueEnum};
+use nix::unistd::Uid;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::Shutdown;
+use std::os::fd::{AsFd, BorrowedFd};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const SOCKET_NAME: &str = "ram-sentinel.sock";
+const SYSTEM_RUNTIME_DIR: &str = "/run";
+
+// A slow or idle client must not stall the monitor loop for long: each connection gets this
+// long in total to send its request (and again to take the response)
+const CLIENT_TIMEOUT_MS: u64 = 500;
+const MAX_REQUEST_BYTES: usize = 64 * 1024;
+// Clients answered per wake; the rest wait for the next one, after the monitor had its turn
+const MAX_CLIENTS_PER_WAKE: usize = 4;
+// list-candidates scans all of /proc, give it time
+const CTL_RESPONSE_TIMEOUT_MS: u64 = 10000;
+// That scan runs on the monitor thread: its answer is reused for this long, however many ask
+const CANDIDATES_CACHE_MS: u64 = 5000;
+
+/// Commands accepted on the control socket, one JSON object per line
+/// (e.g. `{"command":"pause","duration":"10m"}`). Also the syntax of `ram-sentinel ctl`.
+#[derive(Debug, Subcommand, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    /// Current metrics, thresholds, pause/dry-run state and the last kill
+    Status,
+    /// Skip kill actions for a while (e.g. 30s, 10m, 2h). Monitoring and warnings continue.
+    Pause { duration: String },
+    /// End a pause early
+    Resume,
+    /// Turn dry-run mode (as with --no-kill) on or off
+    SetDryRun { state: Switch },
+    /// Processes in the order the kill sequence would pick them
+    ListCandidates,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Switch {
+    On,
+    Off,
+}
+
+/// Runtime overrides changed over the control socket.
+pub struct ControlState {
+    pub dry_run: bool,
+    paused_until: Option<Instant>,
+    candidates: Option<(Instant, Value)>, // Last list-candidates answer and when it was taken
+}
+
+impl ControlState {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            dry_run,
+            paused_until: None,
+            candidates: None,
+        }
+    }
+
+    /// The list-candidates answer and its age, from `list` only if the last one is too old.
+    fn candidates(&mut self, now: Instant, list: impl FnOnce() -> Value) -> (Duration, &Value) {
+        let max_age = Duration::from_millis(CANDIDATES_CACHE_MS);
+        if self
+            .candidates
+            .as_ref()
+            .is_none_or(|(taken, _)| now.saturating_duration_since(*taken) >= max_age)
+        {
+            self.candidates = Some((now, list()));
+        }
+        let (taken, candidates) = self.candidates.as_ref().expect("just filled");
+        (now.saturating_duration_since(*taken), candidates)
+    }
+
+    /// Remaining pause time, if kill actions are paused.
+    pub fn paused_for(&self) -> Option<Duration> {
+        self.paused_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .filter(|left| !left.is_zero())
+    }
+}
+
+/// $XDG_RUNTIME_DIR/ram-sentinel.sock, or /run/ram-sentinel.sock for root.
+/// None if neither applies (we don't fall back to world-writable directories).
+pub fn default_socket_path() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_RUNTIME_DIR").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join(SOCKET_NAME));
+    }
+    Uid::effective()
+        .is_root()
+        .then(|| Path::new(SYSTEM_RUNTIME_DIR).join(SOCKET_NAME))
+}
+
+pub struct ControlServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl ControlServer {
+    pub fn bind(path: &Path) -> io::Result<Self> {
+        if let Ok(meta) = fs::symlink_metadata(path) {
+            if !meta.file_type().is_socket() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    "path exists and is not a socket",
+                ));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another ram-sentinel is already listening",
+                ));
+            }
+            fs::remove_file(path)?; // Stale socket left by a crashed instance
+        }
+        // Owner-only from the start, rather than chmod after bind: no window in which others
+        // could connect. The umask is process-wide, but nothing else creates files meanwhile.
+        let old_mask = unsafe { libc::umask(0o177) };
+        let bound = UnixListener::bind(path);
+        unsafe { libc::umask(old_mask) };
+        let listener = bound?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Polled alongside the PSI triggers so requests are answered without waiting for the next check.
+    pub fn as_fd(&self) -> BorrowedFd<'_> {
+        self.listener.as_fd()
+    }
+
+    /// Answers up to `MAX_CLIENTS_PER_WAKE` pending clients, then returns.
+    pub fn serve(
+        &self,
+        state: &mut ControlState,
+        ctx: &RuntimeContext,
+        monitor: &Monitor,
+        killer: &mut Killer,
+    ) {
+        for _ in 0..MAX_CLIENTS_PER_WAKE {
+            let Ok((stream, _)) = self.listener.accept() else {
+                break;
+            };
+            if let Err(e) = handle_client(stream, state, ctx, monitor, killer) {
+                logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Debug,
+                    text: format!("Control client error: {}", e),
+                });
+            }
+        }
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// One request per connection: reads a line, answers it and closes.
+fn handle_client(
+    mut stream: UnixStream,
+    state: &mut ControlState,
+    ctx: &RuntimeContext,
+    monitor: &Monitor,
+    killer: &mut Killer,
+) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    let line = read_request(&mut stream)?;
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => execute(request, state, ctx, monitor, killer),
+        Err(e) => error_response(format!("Invalid request: {}", e)),
+    };
+    write_response(&mut stream, format!("{}\n", response).as_bytes())
+}
+
+// The first line the client sends, within CLIENT_TIMEOUT_MS overall rather than per read
+fn read_request(stream: &mut UnixStream) -> io::Result<String> {
+    let deadline = Instant::now() + Duration::from_millis(CLIENT_TIMEOUT_MS);
+    let mut request = Vec::new();
+    let mut chunk = [0u8; 1024];
+    while !request.contains(&b'\n') {
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "request too large",
+            ));
+        }
+        stream.set_read_timeout(Some(time_left(deadline)?))?;
+        match stream.read(&mut chunk)? {
+            0 => break, // Closed without a newline: take what was sent
+            n => request.extend_from_slice(&chunk[..n]),
+        }
+    }
+    let line = request.split(|&b| b == b'\n').next().unwrap_or_default();
+    String::from_utf8(line.to_vec()).map_err(io::Error::other)
+}
+
+// Same overall deadline for a client slow to take the response
+fn write_response(stream: &mut UnixStream, mut response: &[u8]) -> io::Result<()> {
+    let deadline = Instant::now() + Duration::from_millis(CLIENT_TIMEOUT_MS);
+    while !response.is_empty() {
+        stream.set_write_timeout(Some(time_left(deadline)?))?;
+        let n = stream.write(response)?;
+        response = &response[n..];
+    }
+    Ok(())
+}
+
+fn time_left(deadline: Instant) -> io::Result<Duration> {
+    Some(deadline.saturating_duration_since(Instant::now()))
+        .filter(|left| !left.is_zero())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "client too slow"))
+}
+
+fn execute(
+    request: Request,
+    state: &mut ControlState,
+    ctx: &RuntimeContext,
+    monitor: &Monitor,
+    killer: &mut Killer,
+) -> Value {
+    match request {
+        Request::Status => {
+            let last_kill = killer.last_kill().map(|(at, event)| {
+                let mut value = serde_json::to_value(event).unwrap_or(Value::Null);
+                if let Some(map) = value.as_object_mut() {
+                    map.insert("timestamp".into(), at.to_rfc3339().into());
+                }
+                value
+            });
+            json!({
+                "ok": true,
+                "dry_run": state.dry_run,
+                "paused_for_ms": state.paused_for().map(|d| d.as_millis() as u64),
+                "metrics": {
+                    "memory_available_bytes": monitor.ram_bytes,
+                    "memory_available_percent": monitor.ram_percent,
+                    "swap_free_bytes": monitor.swap_bytes,
+                    "swap_free_percent": monitor.swap_percent,
+                    "memory_exhaustion_eta_ms": monitor.ram_exhaustion_eta_ms,
+                    "swap_exhaustion_eta_ms": monitor.swap_exhaustion_eta_ms,
+                    "combined_free_bytes": monitor.combined_bytes,
+                    "combined_free_percent": monitor.combined_percent,
+                    "combined_exhaustion_eta_ms": monitor.combined_exhaustion_eta_ms,
+                    "psi_pressure": monitor.psi_pressure,
+                    "psi_line": monitor.psi_metric.map(|m| m.line.as_str()),
+                    "psi_window": monitor.psi_metric.map(|m| m.window.as_str()),
+                    "meminfo": monitor.meminfo,
+                    "swap_usage": monitor.swap_usage,
+                },
+                "thresholds": {
+                    "ram": ctx.source.ram,
+                    "swap": ctx.source.swap,
+                    "combined": ctx.source.combined,
+                    "compressed_swap": ctx.source.compressed_swap,
+                    "meminfo": ctx.source.meminfo,
+                    "psi": ctx.source.psi,
+                    "cgroups": ctx.source.cgroups,
+                },
+                "suspended": killer.suspended_count(),
+                "last_kill": last_kill,
+            })
+        }
+        Request::Pause { duration } => match parse_duration(&duration) {
+            Some(d) if !d.is_zero() => {
+                state.paused_until = Some(Instant::now() + d);
+                log_change(format!("Kill actions paused for {}", duration));
+                json!({ "ok": true, "paused_for_ms": d.as_millis() as u64 })
+            }
+            _ => error_response(format!(
+                "Invalid duration '{}' (expected e.g. 500ms, 30s, 10m, 2h)",
+                duration
+            )),
+        },
+        Request::Resume => {
+            state.paused_until = None;
+            log_change("Kill actions resumed".to_string());
+            json!({ "ok": true })
+        }
+        Request::SetDryRun { state: switch } => {
+            state.dry_run = switch == Switch::On;
+            log_change(format!(
+                "Dry run {}",
+                if state.dry_run { "enabled" } else { "disabled" }
+            ));
+            json!({ "ok": true, "dry_run": state.dry_run })
+        }
+        Request::ListCandidates => {
+            let (age, candidates) =
+                state.candidates(Instant::now(), || json!(killer.list_candidates(ctx)));
+            json!({ "ok": true, "age_ms": age.as_millis() as u64, "candidates": candidates })
+        }
+    }
+}
+
+fn log_change(text: String) {
+    logging::emit(&SentinelEvent::Message {
+        level: LogLevel::Info,
+        text: format!("{} (control socket).", text),
+    });
+}
+
+fn error_response(error: String) -> Value {
+    json!({ "ok": false, "error": error })
+}
+
+/// `ram-sentinel ctl`: sends one request, prints the JSON response and returns the exit code.
+pub fn run_client(socket: Option<PathBuf>, request: &Request) -> i32 {
+    let Some(path) = socket.or_else(default_socket_path) else {
+        logging::emit(&SentinelEvent::Message {
+            level: LogLevel::Error,
+            text: "XDG_RUNTIME_DIR is not set. Pass the control socket path with --socket."
+                .to_string(),
+        });
+        return 1;
+    };
+
+    match send_request(&path, request) {
+        Ok(response) => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&response).unwrap_or_default()
+            );
+            if response["ok"].as_bool() == Some(true) {
+                0
+            } else {
+                1
+            }
+        }
+        Err(e) => {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Error,
+                text: format!("Cannot reach ram-sentinel at {:?}: {}", path, e),
+            });
+            1
+        }
+    }
+}
+
+fn send_request(path: &Path, request: &Request) -> io::Result<Value> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(Duration::from_millis(CTL_RESPONSE_TIMEOUT_MS)))?;
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_first_line_only() {
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        client
+            .write_all(b"{\"command\":\"status\"}\n{\"command\":\"resume\"}\n")
+            .unwrap();
+        assert_eq!(
+            read_request(&mut server).unwrap(),
+            "{\"command\":\"status\"}"
+        );
+    }
+
+    #[test]
+    fn request_without_newline_ends_at_close() {
+        let (mut client, mut server) = UnixStream::pair().unwrap();
+        client.write_all(b"{\"command\":\"resume\"}").unwrap();
+        client.shutdown(Shutdown::Write).unwrap();
+        assert_eq!(
+            read_request(&mut server).unwrap(),
+            "{\"command\":\"resume\"}"
+        );
+    }
+
+    #[test]
+    fn silent_client_times_out() {
+        let (_client, mut server) = UnixStream::pair().unwrap();
+        let started = Instant::now();
+        assert!(read_request(&mut server).is_err());
+        assert!(started.elapsed() < Duration::from_millis(CLIENT_TIMEOUT_MS * 2));
+    }
+
+    #[test]
+    fn candidates_are_reused_until_too_old() {
+        let mut state = ControlState::new(false);
+        let t0 = Instant::now();
+        let (age, list) = state.candidates(t0, || json!([1]));
+        assert_eq!((age, list), (Duration::ZERO, &json!([1])));
+        let later = t0 + Duration::from_millis(CANDIDATES_CACHE_MS - 1);
+        let (age, list) = state.candidates(later, || unreachable!("still fresh"));
+        assert_eq!(age, later - t0);
+        assert_eq!(list, &json!([1]));
+        let stale = t0 + Duration::from_millis(CANDIDATES_CACHE_MS);
+        assert_eq!(state.candidates(stale, || json!([2])).1, &json!([2]));
+    }
+
+    #[test]
+    fn socket_is_created_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let path = env::temp_dir().join(format!("ram-sentinel-test-{}.sock", std::process::id()));
+        let server = ControlServer::bind(&path).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        drop(server);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn bind_refuses_to_replace_a_regular_file() {
+        let path = env::temp_dir().join(format!("ram-sentinel-test-{}", std::process::id()));
+        fs::write(&path, "keep").unwrap();
+        assert!(ControlServer::bind(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "keep");
+        fs::remove_file(&path).unwrap();
+    }
+}
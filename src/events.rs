@@ -1,7 +1,11 @@
+use crate::killer::{ScoreBreakdown, SmapsRollup};
+use crate::meminfo::MemInfo;
+use crate::swap::SwapUsage;
 use byte_unit::Byte;
 use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::path::PathBuf;
 
 // --- Enums ---
 
@@ -71,23 +75,59 @@ pub enum SentinelEvent {
         memory_available_percent: Option<f64>,
         swap_free_bytes: Option<u64>,
         swap_free_percent: Option<f64>,
+        memory_exhaustion_eta_ms: Option<u64>, // None unless free memory is declining
+        swap_exhaustion_eta_ms: Option<u64>,
+        combined_free_bytes: Option<u64>, // Only with a `combined` section
+        combined_free_percent: Option<f64>,
+        combined_exhaustion_eta_ms: Option<u64>,
         psi_pressure: Option<f64>,
+        psi_line: Option<String>,
+        psi_window: Option<String>,
+        meminfo: Option<Box<MemInfo>>, // The full /proc/meminfo sample, including fields without limits
+        swap_usage: Option<Box<SwapUsage>>, // Effective (zram-aware) swap and compressed pool sizes
     },
     LowMemoryWarn {
         available_bytes: u64,
         available_percent: f64,
         threshold_type: String,
         threshold_value: f64,
+        cgroup: Option<String>,
     },
     LowSwapWarn {
-        free_bytes: u64,
+        free_bytes: u64,     // Effective: zram swap counts only for the RAM it can still save
+        raw_free_bytes: u64, // SwapFree as reported
+        free_percent: f64,
+        threshold_type: String,
+        threshold_value: f64,
+    },
+    LowCombinedWarn {
+        free_bytes: u64, // Available RAM + free swap
         free_percent: f64,
         threshold_type: String,
         threshold_value: f64,
     },
+    HighUsageWarn {
+        metric: String, // A /proc/meminfo field (e.g. "Shmem") or "compressed swap"
+        value_bytes: u64,
+        value_percent: f64, // Of MemTotal
+        threshold_type: String,
+        threshold_value: f64,
+        eta_ms: Option<u64>, // Until the kill limit, at the recent rate of growth
+    },
+    ExhaustionPredicted {
+        pool: String, // "memory", "swap" or "memory + swap"
+        free_bytes: u64,
+        decline_bytes_per_sec: u64,
+        eta_ms: u64,
+        horizon_ms: u64,
+        cgroup: Option<String>,
+    },
     PsiPressureWarn {
         pressure_curr: f64,
         threshold: f64,
+        line: String,
+        window: String,
+        cgroup: Option<String>,
     },
     KillTriggered {
         trigger: String,
@@ -95,6 +135,11 @@ pub enum SentinelEvent {
         threshold_value: f64,
         threshold_type: String,
         amount_needed: Option<u64>,
+        psi_line: Option<String>,
+        psi_window: Option<String>,
+        cgroup: Option<String>,
+        held_ms: u64,      // How long the condition persisted before firing
+        held_samples: u32, // Consecutive breaching checks, including this one
     },
     KillCandidateSelected {
         pid: u32,
@@ -102,20 +147,56 @@ pub enum SentinelEvent {
         score: u64,
         rss: u64,
         match_index: usize,
+        breakdown: Option<ScoreBreakdown>, // Per-factor points, `weighted` strategy only
+        swap: Option<u64>,                 // VmSwap, read for swap-triggered kills
+        smaps_rollup: Option<SmapsRollup>, // Read unless killAccounting is `rss`
     },
     KillExecuted {
         pid: u32,
         process_name: String,
         strategy: String,
+        mechanism: String,
         rss_freed: u64,
+        estimated_freed: u64,        // Deducted from the amount needed
+        accounting: String,          // What estimated_freed is: rss, pss, uss or memory.current
+        observed_freed: Option<u64>, // Growth of the relieved pool once the victim was gone
+        smaps_rollup: Option<SmapsRollup>,
+        time_to_exit_ms: Option<u64>,
+        expedited_reclaim: Option<bool>,
+        cgroup: Option<String>, // Set when the whole cgroup was killed
+    },
+    ProcessFrozen {
+        pid: u32,
+        process_name: String,
+        action: String, // "freeze" (cgroup.freeze) or "stop" (SIGSTOP)
+        cgroup: Option<String>,
+        rss: u64,
+    },
+    ProcessThawed {
+        pid: u32,
+        process_name: String,
+        action: String,
+        cgroup: Option<String>,
+        suspended_ms: u64,
     },
     KillSequenceAborted {
         reason: String,
     },
     KillCandidateIgnored {
         pid: u32,
+        mechanism: String,
         reason: String,
     },
+    ConfigReloaded {
+        trigger: String,
+        path: Option<PathBuf>,
+        changed_fields: Vec<String>,
+    },
+    ConfigReloadFailed {
+        trigger: String,
+        path: Option<PathBuf>,
+        error: String,
+    },
 }
 
 // --- Display Implementation (for Compact Mode) ---
@@ -132,7 +213,16 @@ impl fmt::Display for SentinelEvent {
                 memory_available_percent: _,
                 swap_free_bytes,
                 swap_free_percent: _,
+                memory_exhaustion_eta_ms,
+                swap_exhaustion_eta_ms,
+                combined_free_bytes,
+                combined_free_percent: _,
+                combined_exhaustion_eta_ms,
                 psi_pressure,
+                psi_line,
+                psi_window,
+                meminfo: _,
+                swap_usage,
             } => {
                 let avail_str = match memory_available_bytes {
                     Some(b) => Byte::from_u64(*b)
@@ -148,15 +238,51 @@ impl fmt::Display for SentinelEvent {
                     None => "N/A".to_string(),
                 };
 
-                let psi_str = match psi_pressure {
-                    Some(p) => format!("{:.2}", p),
-                    None => "N/A".to_string(),
+                // Only shown with zram/zswap in use
+                let compressed_str = match swap_usage {
+                    Some(u) if u.compressed_stored() > 0 => format!(
+                        " ({} compressed into {} of RAM)",
+                        Byte::from_u64(u.compressed_stored())
+                            .get_appropriate_unit(byte_unit::UnitType::Decimal),
+                        Byte::from_u64(u.compressed_physical())
+                            .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    ),
+                    _ => String::new(),
+                };
+
+                let psi_str = match (psi_pressure, psi_line, psi_window) {
+                    (Some(p), Some(line), Some(window)) => {
+                        format!("{:.2} ({} {})", p, line, window)
+                    }
+                    (Some(p), _, _) => format!("{:.2}", p),
+                    (None, _, _) => "N/A".to_string(),
+                };
+
+                // Only shown while declining
+                let eta_str = |eta: &Option<u64>| match eta {
+                    Some(ms) => format!(" (exhausted in ~{}s)", ms / 1000),
+                    None => String::new(),
+                };
+
+                let combined_str = match combined_free_bytes {
+                    Some(b) => format!(
+                        ", Combined: {} available{}",
+                        Byte::from_u64(*b).get_appropriate_unit(byte_unit::UnitType::Decimal),
+                        eta_str(combined_exhaustion_eta_ms)
+                    ),
+                    None => String::new(),
                 };
 
                 write!(
                     f,
-                    "Memory: {} available, Swap: {} available, PSI: {}",
-                    avail_str, swap_str, psi_str
+                    "Memory: {} available{}, Swap: {} available{}{}{}, PSI: {}",
+                    avail_str,
+                    eta_str(memory_exhaustion_eta_ms),
+                    swap_str,
+                    compressed_str,
+                    eta_str(swap_exhaustion_eta_ms),
+                    combined_str,
+                    psi_str
                 )
             }
             SentinelEvent::LowMemoryWarn {
@@ -164,7 +290,9 @@ impl fmt::Display for SentinelEvent {
                 available_percent,
                 threshold_type,
                 threshold_value,
+                cgroup,
             } => {
+                let scope = scope_prefix(cgroup);
                 let avail_str = Byte::from_u64(*available_bytes)
                     .get_appropriate_unit(byte_unit::UnitType::Decimal)
                     .to_string();
@@ -174,19 +302,67 @@ impl fmt::Display for SentinelEvent {
                         .to_string();
                     write!(
                         f,
-                        "Low RAM: {} available (Limit: {})",
-                        avail_str, thresh_str
+                        "{}Low RAM: {} available (Limit: {})",
+                        scope, avail_str, thresh_str
                     )
                 } else {
                     write!(
                         f,
-                        "Low RAM: {} ({:.2}%) available (Limit: {:.2}%)",
-                        avail_str, available_percent, threshold_value
+                        "{}Low RAM: {} ({:.2}%) available (Limit: {:.2}%)",
+                        scope, avail_str, available_percent, threshold_value
                     )
                 }
             }
+            SentinelEvent::HighUsageWarn {
+                metric,
+                value_bytes,
+                value_percent,
+                threshold_type,
+                threshold_value,
+                eta_ms,
+            } => {
+                let value_str = Byte::from_u64(*value_bytes)
+                    .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    .to_string();
+                if threshold_type == "bytes" {
+                    let thresh_str = Byte::from_u64(*threshold_value as u64)
+                        .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                        .to_string();
+                    write!(f, "High {}: {} (Limit: {})", metric, value_str, thresh_str)?;
+                } else {
+                    write!(
+                        f,
+                        "High {}: {} ({:.2}% of RAM) (Limit: {:.2}%)",
+                        metric, value_str, value_percent, threshold_value
+                    )?;
+                }
+                match eta_ms {
+                    Some(eta) => write!(f, ", kill limit reached in ~{}ms", eta),
+                    None => Ok(()),
+                }
+            }
+            SentinelEvent::ExhaustionPredicted {
+                pool,
+                free_bytes,
+                decline_bytes_per_sec,
+                eta_ms,
+                horizon_ms,
+                cgroup,
+            } => {
+                let scope = scope_prefix(cgroup);
+                let free_str =
+                    Byte::from_u64(*free_bytes).get_appropriate_unit(byte_unit::UnitType::Decimal);
+                let rate_str = Byte::from_u64(*decline_bytes_per_sec)
+                    .get_appropriate_unit(byte_unit::UnitType::Decimal);
+                write!(
+                    f,
+                    "{}Low {} predicted: {} free, shrinking {}/s, limit reached in ~{}ms (Horizon: {}ms)",
+                    scope, pool, free_str, rate_str, eta_ms, horizon_ms
+                )
+            }
             SentinelEvent::LowSwapWarn {
                 free_bytes,
+                raw_free_bytes,
                 free_percent,
                 threshold_type,
                 threshold_value,
@@ -194,19 +370,55 @@ impl fmt::Display for SentinelEvent {
                 let free_str = Byte::from_u64(*free_bytes)
                     .get_appropriate_unit(byte_unit::UnitType::Decimal)
                     .to_string();
+                // Differs only when zram swap is in use
+                let raw_str = if raw_free_bytes != free_bytes {
+                    format!(
+                        " (zram-adjusted; {} as reported)",
+                        Byte::from_u64(*raw_free_bytes)
+                            .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    )
+                } else {
+                    String::new()
+                };
                 if threshold_type == "bytes" {
                     let thresh_str = Byte::from_u64(*threshold_value as u64)
                         .get_appropriate_unit(byte_unit::UnitType::Decimal)
                         .to_string();
                     write!(
                         f,
-                        "Low Swap: {} available (Limit: {})",
+                        "Low Swap: {} available{} (Limit: {})",
+                        free_str, raw_str, thresh_str
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Low Swap: {} ({:.2}%) available{} (Limit: {:.2}%)",
+                        free_str, free_percent, raw_str, threshold_value
+                    )
+                }
+            }
+            SentinelEvent::LowCombinedWarn {
+                free_bytes,
+                free_percent,
+                threshold_type,
+                threshold_value,
+            } => {
+                let free_str = Byte::from_u64(*free_bytes)
+                    .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    .to_string();
+                if threshold_type == "bytes" {
+                    let thresh_str = Byte::from_u64(*threshold_value as u64)
+                        .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                        .to_string();
+                    write!(
+                        f,
+                        "Low RAM + Swap: {} available (Limit: {})",
                         free_str, thresh_str
                     )
                 } else {
                     write!(
                         f,
-                        "Low Swap: {} ({:.2}%) available (Limit: {:.2}%)",
+                        "Low RAM + Swap: {} ({:.2}%) available (Limit: {:.2}%)",
                         free_str, free_percent, threshold_value
                     )
                 }
@@ -214,11 +426,18 @@ impl fmt::Display for SentinelEvent {
             SentinelEvent::PsiPressureWarn {
                 pressure_curr,
                 threshold,
+                line,
+                window,
+                cgroup,
             } => {
                 write!(
                     f,
-                    "Memory Pressure: {:.2}% (Limit: {:.2}%)",
-                    pressure_curr, threshold
+                    "{}Memory Pressure ({} {}): {:.2}% (Limit: {:.2}%)",
+                    scope_prefix(cgroup),
+                    line,
+                    window,
+                    pressure_curr,
+                    threshold
                 )
             }
             SentinelEvent::KillTriggered {
@@ -226,69 +445,258 @@ impl fmt::Display for SentinelEvent {
                 observed_value,
                 threshold_value,
                 threshold_type,
+                psi_line,
+                psi_window,
+                cgroup,
+                held_ms,
+                held_samples,
                 ..
             } => {
-                let observed_str = if threshold_type == "bytes" {
-                    Byte::from_u64(*observed_value as u64)
-                        .get_appropriate_unit(byte_unit::UnitType::Decimal)
-                        .to_string()
+                let scope = scope_prefix(cgroup);
+                let held = if *held_samples > 1 {
+                    format!(" (held {}ms over {} checks)", held_ms, held_samples)
                 } else {
-                    format!("{:.2}%", observed_value)
+                    String::new()
                 };
-                let limit_str = if threshold_type == "bytes" {
-                    Byte::from_u64(*threshold_value as u64)
+                let format_value = |value: f64| match threshold_type.as_str() {
+                    "bytes" => Byte::from_u64(value as u64)
                         .get_appropriate_unit(byte_unit::UnitType::Decimal)
-                        .to_string()
-                } else {
-                    format!("{:.2}%", threshold_value)
+                        .to_string(),
+                    "ms" => format!("{}ms", value as u64), // Predicted time to exhaustion
+                    _ => format!("{:.2}%", value),
                 };
-                write!(
-                    f,
-                    "Kill Triggered: {} - Observed {} < Limit {}",
-                    trigger, observed_str, limit_str
-                )
+                let observed_str = format_value(*observed_value);
+                let limit_str = format_value(*threshold_value);
+                if let (Some(line), Some(window)) = (psi_line, psi_window) {
+                    // Pressure breaches upwards, unlike free memory
+                    write!(
+                        f,
+                        "{}Kill Triggered: {} ({} {}) - Observed {} > Limit {}{}",
+                        scope, trigger, line, window, observed_str, limit_str, held
+                    )
+                } else {
+                    // Free memory breaches downwards, /proc/meminfo usage limits upwards
+                    let op = if observed_value > threshold_value {
+                        ">"
+                    } else {
+                        "<"
+                    };
+                    write!(
+                        f,
+                        "{}Kill Triggered: {} - Observed {} {} Limit {}{}",
+                        scope, trigger, observed_str, op, limit_str, held
+                    )
+                }
             }
             SentinelEvent::KillCandidateSelected {
                 process_name,
                 pid,
                 score,
                 rss,
+                breakdown,
+                swap,
+                smaps_rollup,
                 ..
             } => {
                 let rss_str = Byte::from_u64(*rss)
                     .get_appropriate_unit(byte_unit::UnitType::Decimal)
                     .to_string();
+                // Factors that contributed, e.g. " (rss 412.3 + targetIndex 1000.0)"
+                let factors = breakdown.map_or(String::new(), |b| {
+                    let terms: Vec<String> = [
+                        ("rss", b.rss),
+                        ("pss", b.pss),
+                        ("swap", b.swap),
+                        ("oomScore", b.oom_score),
+                        ("age", b.age),
+                        ("cpuIdle", b.cpu_idle),
+                        ("targetIndex", b.target_index),
+                    ]
+                    .iter()
+                    .filter(|(_, points)| *points != 0.0)
+                    .map(|(name, points)| format!("{} {:.1}", name, points))
+                    .collect();
+                    format!(" ({})", terms.join(" + "))
+                });
                 write!(
                     f,
-                    "Selected Target: {} (PID {}). Score: {}, RSS: {}",
-                    process_name, pid, score, rss_str
-                )
+                    "Selected Target: {} (PID {}). Score: {}{}, RSS: {}",
+                    process_name, pid, score, factors, rss_str
+                )?;
+                if let Some(s) = smaps_rollup {
+                    write!(
+                        f,
+                        ", PSS: {}, USS: {}",
+                        Byte::from_u64(s.pss).get_appropriate_unit(byte_unit::UnitType::Decimal),
+                        Byte::from_u64(s.uss).get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    )?;
+                }
+                match swap {
+                    Some(swap) => write!(
+                        f,
+                        ", Swap: {}",
+                        Byte::from_u64(*swap).get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    ),
+                    None => Ok(()),
+                }
             }
             SentinelEvent::KillExecuted {
                 process_name,
                 pid,
                 strategy,
+                mechanism,
                 rss_freed,
+                estimated_freed,
+                accounting,
+                observed_freed,
+                time_to_exit_ms,
+                expedited_reclaim,
+                cgroup,
+                ..
             } => {
-                let rss_str = Byte::from_u64(*rss_freed)
+                let freed_str = Byte::from_u64(*estimated_freed)
                     .get_appropriate_unit(byte_unit::UnitType::Decimal)
                     .to_string();
                 write!(
                     f,
-                    "{} {} (PID {}). Freed: {}",
-                    strategy, process_name, pid, rss_str
-                )
+                    "{} {} (PID {}) via {}. Freed: {}",
+                    strategy, process_name, pid, mechanism, freed_str
+                )?;
+                if accounting == "pss" || accounting == "uss" {
+                    let rss_str = Byte::from_u64(*rss_freed)
+                        .get_appropriate_unit(byte_unit::UnitType::Decimal);
+                    write!(f, " by {} (RSS {})", accounting.to_uppercase(), rss_str)?;
+                }
+                if let Some(observed) = observed_freed {
+                    let observed_str = Byte::from_u64(*observed)
+                        .get_appropriate_unit(byte_unit::UnitType::Decimal);
+                    write!(f, ", observed: {}", observed_str)?;
+                }
+                if let Some(path) = cgroup {
+                    write!(f, " (whole cgroup {})", path)?;
+                }
+                if let Some(ms) = time_to_exit_ms {
+                    write!(f, " (exited after {}ms)", ms)?;
+                }
+                match expedited_reclaim {
+                    Some(true) => write!(f, " (reclaim expedited)"),
+                    Some(false) => write!(f, " (expedited reclaim unavailable)"),
+                    None => Ok(()),
+                }
+            }
+            SentinelEvent::ProcessFrozen {
+                pid,
+                process_name,
+                action,
+                cgroup,
+                rss,
+            } => {
+                let rss_str = Byte::from_u64(*rss)
+                    .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    .to_string();
+                let verb = if action == "freeze" {
+                    "Froze"
+                } else {
+                    "Stopped"
+                };
+                write!(
+                    f,
+                    "{} {} (PID {}). RSS: {}",
+                    verb, process_name, pid, rss_str
+                )?;
+                if let Some(path) = cgroup {
+                    write!(f, " (whole cgroup {})", path)?;
+                }
+                Ok(())
+            }
+            SentinelEvent::ProcessThawed {
+                pid,
+                process_name,
+                action,
+                cgroup,
+                suspended_ms,
+            } => {
+                let verb = if action == "freeze" {
+                    "Thawed"
+                } else {
+                    "Continued"
+                };
+                write!(
+                    f,
+                    "{} {} (PID {}) after {}ms",
+                    verb, process_name, pid, suspended_ms
+                )?;
+                if let Some(path) = cgroup {
+                    write!(f, " (whole cgroup {})", path)?;
+                }
+                Ok(())
             }
             SentinelEvent::KillSequenceAborted { reason } => {
                 write!(f, "Kill Sequence Aborted: {}", reason)
             }
-            SentinelEvent::KillCandidateIgnored { pid, reason } => {
-                write!(f, "Ignored Candidate PID {}: {}", pid, reason)
+            SentinelEvent::KillCandidateIgnored {
+                pid,
+                mechanism,
+                reason,
+            } => {
+                write!(
+                    f,
+                    "Ignored Candidate PID {} ({}): {}",
+                    pid, mechanism, reason
+                )
+            }
+            SentinelEvent::ConfigReloaded {
+                trigger,
+                path,
+                changed_fields,
+            } => {
+                let source = match path {
+                    Some(p) => format!("{:?}", p),
+                    None => "sane defaults".to_string(),
+                };
+                if changed_fields.is_empty() {
+                    write!(
+                        f,
+                        "Configuration reloaded from {} ({}). No changes.",
+                        source, trigger
+                    )
+                } else {
+                    write!(
+                        f,
+                        "Configuration reloaded from {} ({}). Changed: {}",
+                        source,
+                        trigger,
+                        changed_fields.join(", ")
+                    )
+                }
+            }
+            SentinelEvent::ConfigReloadFailed {
+                trigger,
+                path,
+                error,
+            } => {
+                let source = match path {
+                    Some(p) => format!("{:?}", p),
+                    None => "sane defaults".to_string(),
+                };
+                write!(
+                    f,
+                    "Configuration reload from {} ({}) failed, keeping previous configuration: {}",
+                    source, trigger, error
+                )
             }
         }
     }
 }
 
+// "[cgroup /path] " for events raised by a scoped cgroup monitor
+fn scope_prefix(cgroup: &Option<String>) -> String {
+    match cgroup {
+        Some(path) => format!("[cgroup {}] ", path),
+        None => String::new(),
+    }
+}
+
 impl SentinelEvent {
     /// Determines the log severity of the current event.
     pub fn severity(&self) -> LogLevel {
@@ -300,14 +708,22 @@ impl SentinelEvent {
             SentinelEvent::Startup { .. }
             | SentinelEvent::KillCandidateSelected { .. }
             | SentinelEvent::KillExecuted { .. }
+            | SentinelEvent::ProcessFrozen { .. }
+            | SentinelEvent::ProcessThawed { .. }
             | SentinelEvent::KillSequenceAborted { .. }
-            | SentinelEvent::KillCandidateIgnored { .. } => LogLevel::Info,
+            | SentinelEvent::KillCandidateIgnored { .. }
+            | SentinelEvent::ConfigReloaded { .. } => LogLevel::Info,
 
             SentinelEvent::LowMemoryWarn { .. }
             | SentinelEvent::LowSwapWarn { .. }
+            | SentinelEvent::LowCombinedWarn { .. }
+            | SentinelEvent::HighUsageWarn { .. }
+            | SentinelEvent::ExhaustionPredicted { .. }
             | SentinelEvent::PsiPressureWarn { .. } => LogLevel::Warn,
 
-            SentinelEvent::KillTriggered { .. } => LogLevel::Error,
+            SentinelEvent::KillTriggered { .. } | SentinelEvent::ConfigReloadFailed { .. } => {
+                LogLevel::Error
+            }
         }
     }
 }
@@ -1,28 +1,185 @@
-use crate::config::{KillStrategy, RuntimeContext};
-use crate::events::SentinelEvent;
+use crate::cgroup::{MemoryFiles, ScopePattern, cgroup_dir, is_app_unit, unified_cgroup_path};
+use crate::config::{KillAccounting, KillStrategy, KillWeights, RuntimeContext, TargetAction};
+use crate::events::{LogLevel, SentinelEvent};
 use crate::logging;
+use crate::matcher::ProcFacts;
+use crate::meminfo::{MemInfoField, MemInfoReader, ProcStat, kb_field, parse_stat};
+use crate::pidfd::PidFd;
+use crate::swap::SwapReader;
+use crate::utils::get_total_memory;
+use chrono::{DateTime, Utc};
+use nix::errno::Errno;
 use nix::sys::signal::{Signal, kill};
 use nix::unistd::{Pid as NixPid, SysconfVar, Uid, sysconf};
+use serde::Serialize;
 use std::fmt::Write; // For writing to path_buffer
 use std::fs::{self, File};
 use std::io::Read;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+// Signal delivery mechanisms, as reported in events
+const MECHANISM_PIDFD: &str = "pidfd";
+const MECHANISM_PID: &str = "pid";
+const MECHANISM_CGROUP_KILL: &str = "cgroup.kill";
+
+// Fallback liveness polling interval when no pidfd is available
+const EXIT_POLL_INTERVAL_MS: u64 = 50;
+
+// How long to wait for a SIGKILLed victim to be gone before measuring what it freed
+const SIGKILL_SETTLE_MS: u64 = 1000;
+
+// Upper bound on kills in one sequence, whatever the measurements say
+const MAX_KILLS_PER_SEQUENCE: usize = 16;
+// A victim that didn't exit in time is credited estimate / this
+const MIN_CREDIT_DIVISOR: u64 = 4;
+
+// What a kill gave back to the relieved pool, as far as it could be measured
+#[derive(Clone, Copy)]
+enum Measured {
+    Freed(u64),
+    Unreadable, // The pool couldn't be sampled before or after
+    NotGone,    // The victim was still around when measured
+}
+
+impl Measured {
+    fn observed(self) -> Option<u64> {
+        match self {
+            Measured::Freed(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+}
+
+enum WaitOutcome {
+    Exited(Duration),
+    PidReused,
+    TimedOut,
+}
 
 pub struct Killer {
     // Buffers for zero-allocation logic
     read_buffer: Vec<u8>,
     path_buffer: String,
+    facts: ProcFacts, // For matchers beyond the cmdline
     page_size: u64,
+    clock_ticks: u64,          // Per second, the unit of /proc/[pid]/stat times
+    suspended: Vec<Suspended>, // Frozen/stopped targets, in suspension order
+    last_kill: Option<(DateTime<Utc>, SentinelEvent)>,
+    // Own readers (separate from the monitor's) to measure what each kill freed
+    meminfo_reader: Option<MemInfoReader>,
+    swap_reader: Option<SwapReader>,
+}
+
+/// The pool a kill sequence has to refill; each kill is measured there.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Relief {
+    Memory,   // MemAvailable
+    Swap,     // Effective free swap (see SwapUsage)
+    Combined, // Both
+}
+
+impl Relief {
+    /// For a `meminfo` limit: fields that only shrink as swapped-out or swap-cached pages go
+    /// away are relieved by victims holding swap; the rest by freeing RAM.
+    pub fn for_meminfo(field: MemInfoField) -> Self {
+        match field {
+            MemInfoField::SwapCached | MemInfoField::Zswap | MemInfoField::Zswapped => Relief::Swap,
+            MemInfoField::Dirty
+            | MemInfoField::Writeback
+            | MemInfoField::Shmem
+            | MemInfoField::Mlocked
+            | MemInfoField::Unevictable => Relief::Memory,
+        }
+    }
+}
+
+/// A process as it would be ranked by the kill sequence (see `Killer::list_candidates`).
+#[derive(Debug, Serialize)]
+pub struct Candidate {
+    pub pid: u32,
+    pub process_name: String,
+    pub match_index: Option<usize>, // None: matches no killTargets entry
+    pub target_pattern: Option<String>,
+    pub ignored_by: Option<String>, // ignoreNames entry protecting it (never killed)
+    pub score: u64,
+    pub rss: u64,
+    pub estimate: u64, // What killing it would count as freed (killAccounting)
+    pub smaps_rollup: Option<SmapsRollup>, // Read unless killAccounting is `rss`
+    pub breakdown: Option<ScoreBreakdown>, // `weighted` strategy only
+}
+
+/// Memory figures from /proc/[pid]/smaps_rollup (Linux 4.14+), in bytes.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct SmapsRollup {
+    pub pss: u64,
+    pub pss_anon: Option<u64>, // The Pss_* split needs Linux 5.9+
+    pub pss_shmem: Option<u64>,
+    pub uss: u64, // Private_Clean + Private_Dirty
+    pub swap: u64,
+}
+
+/// How a `weighted` score came about: each factor's points (weight x factor scaled to 0-1000).
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct ScoreBreakdown {
+    pub rss: f64,
+    pub pss: f64,
+    pub swap: f64,
+    pub oom_score: f64,
+    pub age: f64,
+    pub cpu_idle: f64,
+    pub target_index: f64,
+    pub total: f64,
+}
+
+// What read_score found out about a process; fields the strategy didn't need stay empty
+struct Scored {
+    score: u64,
+    rss: u64,
+    swap: Option<u64>, // VmSwap
+    smaps: Option<SmapsRollup>,
+    breakdown: Option<ScoreBreakdown>,
+}
+
+// Result of the per-process filters in the /proc scan
+struct Classified {
+    pid: u32,
+    match_index: usize,        // usize::MAX for non-matches
+    ignored_by: Option<usize>, // Index into ignoreNames
+}
+
+// A target paused by a `freeze`/`stop` action, awaiting thaw
+struct Suspended {
+    pid: u32,
+    process_name: String,
+    start_time: u64,
+    since: Instant,
+    hold: Hold,
 }
 
-#[derive(Debug, Clone)]
+enum Hold {
+    Frozen { cgroup: String, scope: ScopePattern },
+    Stopped(Option<PidFd>),
+}
+
+// What one step of a kill sequence did
+enum Step {
+    Freed(u64), // Counts against the deficit; 0 if the victim was already gone
+    Suspended,  // Paused: it keeps its memory, so the sequence ends here
+}
+
+#[derive(Debug)]
 struct Champion {
     pid: u32,
-    score: u64,         // Sorting metric (RSS or OOM Score)
-    rss: u64,           // Actual memory usage in bytes
-    match_index: usize, // 0..N for explicit targets, usize::MAX for non-matches
-    start_time: u64,    // From /proc/[pid]/stat (for safety check)
+    score: u64,                        // Sorting metric (RSS or OOM Score)
+    rss: u64,                          // Actual memory usage in bytes
+    match_index: usize,                // 0..N for explicit targets, usize::MAX for non-matches
+    start_time: u64,                   // From /proc/[pid]/stat (for safety check)
+    breakdown: Option<ScoreBreakdown>, // `weighted` strategy: ranks by its total instead
+    swap: Option<u64>,                 // VmSwap, read for swap-triggered kills
+    smaps: Option<SmapsRollup>,
+    estimate: u64, // Freed estimate per killAccounting, deducted from amount_needed
+    accounting: &'static str, // Which figure `estimate` is
 }
 
 impl Killer {
@@ -32,21 +189,60 @@ impl Killer {
             Ok(Some(val)) => val as u64,
             _ => 4096,
         };
+        let clock_ticks = match sysconf(SysconfVar::CLK_TCK) {
+            Ok(Some(val)) => val as u64,
+            _ => 100,
+        };
 
         Self {
             // Pre-allocate AND initialize to ensure pages are physically backed (prevent page faults during OOM)
             read_buffer: vec![0u8; 256 * 1024],
             path_buffer: String::with_capacity(256),
+            facts: ProcFacts::new(),
             page_size,
+            clock_ticks,
+            suspended: Vec::new(),
+            last_kill: None,
+            meminfo_reader: MemInfoReader::new().ok(),
+            swap_reader: SwapReader::new().ok(),
         }
     }
 
-    pub fn kill_sequence(&mut self, ctx: &RuntimeContext, mut amount_needed: Option<u64>) {
+    /// `trigger_scope` confines candidates to the cgroup that raised the kill (if any),
+    /// on top of the configured `killScope`. Each kill counts towards `amount_needed` with what
+    /// it measurably freed in the `relief` pool, or its estimate if that can't be measured
+    /// (a quarter of it if the victim didn't exit in time).
+    /// Pausing a target ends the sequence. Targets paused by an earlier sequence are killed first
+    /// once they had `pauseGraceMs` to take effect; until then the sequence does nothing.
+    pub fn kill_sequence(
+        &mut self,
+        ctx: &RuntimeContext,
+        mut amount_needed: Option<u64>,
+        trigger_scope: Option<&ScopePattern>,
+        relief: Relief,
+    ) {
+        let mut kills = 0;
         loop {
-            // 1. Scan /proc and find the best candidate ("The Champion")
-            let champion_opt = self.find_champion(ctx);
-
-            if let Some(champion) = champion_opt {
+            // 1. Targets paused during an earlier breach didn't prevent this one: kill them before
+            // anything else is paused or killed, oldest (most expendable) first. A target paused
+            // moments ago is given time instead (e.g. to be swapped out). Otherwise scan /proc
+            // and find the best candidate ("The Champion")
+            let (pid, name, step) = if let Some(paused) = self.take_overdue(ctx, trigger_scope) {
+                let (pid, name) = (paused.pid, paused.process_name.clone());
+                (pid, name, self.escalate(paused, relief))
+            } else if let Some(index) = self.oldest_paused(ctx, trigger_scope) {
+                let settling = &self.suspended[index];
+                logging::emit(&SentinelEvent::KillSequenceAborted {
+                    reason: format!(
+                        "PID {} {} was paused {}ms ago. Waiting up to {}ms for that to take effect.",
+                        settling.pid,
+                        settling.process_name,
+                        settling.since.elapsed().as_millis(),
+                        ctx.pause_grace_ms
+                    ),
+                });
+                break;
+            } else if let Some(champion) = self.find_champion(ctx, trigger_scope, relief) {
                 // Fetch name for logging (on-demand, after scan loop)
                 let name = self
                     .get_process_name(champion.pid)
@@ -58,40 +254,62 @@ impl Killer {
                     score: champion.score,
                     rss: champion.rss,
                     match_index: champion.match_index,
+                    breakdown: champion.breakdown,
+                    swap: champion.swap,
+                    smaps_rollup: champion.smaps,
                 });
 
                 // 2. Kill Logic
-                match self.kill_process(ctx, &champion, &name) {
-                    Some(freed_bytes) => {
-                        if let Some(needed) = amount_needed {
-                            if freed_bytes >= needed {
-                                logging::emit(&SentinelEvent::KillSequenceAborted {
-                                    reason: format!("Freed {} bytes. Target reached.", freed_bytes),
-                                });
-                                break;
-                            } else {
-                                amount_needed = Some(needed - freed_bytes);
-                            }
-                        } else {
-                            // If no specific amount was requested, stop after one kill
-                            break;
-                        }
-                    }
-                    None => {
-                        logging::emit(&SentinelEvent::KillSequenceAborted {
-                            reason: format!(
-                                "Failed to kill victim PID {} {}. Aborting.",
-                                champion.pid, name
-                            ),
-                        });
-                        break;
-                    }
-                }
+                let step = self.kill_process(ctx, &champion, &name, relief);
+                (champion.pid, name, step)
             } else {
                 logging::emit(&SentinelEvent::KillSequenceAborted {
                     reason: "No eligible kill candidates found!".to_string(),
                 });
                 break;
+            };
+
+            match step {
+                Some(Step::Freed(freed_bytes)) => {
+                    if let Some(needed) = amount_needed {
+                        if freed_bytes >= needed {
+                            logging::emit(&SentinelEvent::KillSequenceAborted {
+                                reason: format!("Freed {} bytes. Target reached.", freed_bytes),
+                            });
+                            break;
+                        }
+                        amount_needed = Some(needed - freed_bytes);
+                        kills += 1;
+                        if kills >= MAX_KILLS_PER_SEQUENCE {
+                            logging::emit(&SentinelEvent::KillSequenceAborted {
+                                reason: format!(
+                                    "Stopped after {} kills, {} bytes still needed.",
+                                    kills,
+                                    needed - freed_bytes
+                                ),
+                            });
+                            break;
+                        }
+                    } else {
+                        // If no specific amount was requested, stop after one kill
+                        break;
+                    }
+                }
+                Some(Step::Suspended) => {
+                    logging::emit(&SentinelEvent::KillSequenceAborted {
+                        reason: format!(
+                            "Paused PID {} {}. It is killed if memory runs short again before it is resumed.",
+                            pid, name
+                        ),
+                    });
+                    break;
+                }
+                None => {
+                    logging::emit(&SentinelEvent::KillSequenceAborted {
+                        reason: format!("Failed to kill victim PID {} {}. Aborting.", pid, name),
+                    });
+                    break;
+                }
             }
         }
     }
@@ -108,12 +326,16 @@ impl Killer {
 
     /// The "Hunter" Loop: Scans /proc manually to find the best kill candidate
     /// This avoids large allocations by reusing internal buffers.
-    fn find_champion(&mut self, ctx: &RuntimeContext) -> Option<Champion> {
+    /// With `Relief::Swap`, ranks by swap usage instead (see `read_score`).
+    fn find_champion(
+        &mut self,
+        ctx: &RuntimeContext,
+        trigger_scope: Option<&ScopePattern>,
+        relief: Relief,
+    ) -> Option<Champion> {
         let current_uid = Uid::effective();
-        let is_root = current_uid.is_root();
-        let my_pid = std::process::id();
-
         let mut current_champion: Option<Champion> = None;
+        let weighted = ctx.kill_strategy == KillStrategy::Weighted;
 
         // Manual /proc implementation using std::fs::read_dir
         let entries = match fs::read_dir("/proc") {
@@ -126,193 +348,409 @@ impl Killer {
             }
         };
 
-        for entry in entries {
-            if let Ok(entry) = entry {
-                // Get filename (PID)
-                let file_name = entry.file_name();
-                let file_name_str = match file_name.to_str() {
-                    Some(s) => s,
-                    None => continue,
-                };
+        for entry in entries.flatten() {
+            // Get filename (PID)
+            let file_name = entry.file_name();
+            let file_name_str = match file_name.to_str() {
+                Some(s) => s,
+                None => continue,
+            };
 
-                // Filter 1: Must be PID (numeric)
-                let pid: u32 = match file_name_str.parse() {
-                    Ok(p) => p,
-                    Err(_) => continue,
-                };
+            let Some(Classified {
+                pid,
+                match_index,
+                ignored_by: None,
+            }) = self.classify(&entry, file_name_str, ctx, trigger_scope, current_uid)
+            else {
+                continue;
+            };
 
-                // Filter 2: Never kill self
-                if pid == my_pid {
-                    continue;
-                }
+            // Check vs Current Champion (Optimization). With `weighted`, the index is only a factor
+            if !weighted
+                && let Some(champ) = &current_champion
+                && match_index > champ.match_index
+            {
+                continue;
+            }
 
-                // Filter 3: Ownership Check (if not root)
-                if !is_root {
-                    use std::os::unix::fs::MetadataExt;
-                    // Avoid stat call if possible, but we need UID. entry.metadata() is cached from readdir? No, usually distinct.
-                    if let Ok(meta) = entry.metadata() {
-                        if meta.uid() != current_uid.as_raw() {
-                            continue;
-                        }
-                    } else {
+            // B. Calculate Score & RSS
+            let Scored {
+                score,
+                rss,
+                swap,
+                smaps,
+                breakdown,
+            } = self.read_score(file_name_str, ctx, match_index, relief);
+
+            // Final Comparison
+            if let Some(champ) = &current_champion {
+                if let (Some(new), Some(old)) = (breakdown, champ.breakdown) {
+                    if new.total <= old.total {
+                        continue;
+                    }
+                } else if match_index == champ.match_index {
+                    if score <= champ.score {
                         continue;
                     }
+                } else if match_index > champ.match_index {
+                    continue;
                 }
+            }
 
-                // ---------------------------------------------------------
-                // Analyze Process
-                // ---------------------------------------------------------
+            // C. Become the Champion (Read stat for Start Time)
+            if let Some(st) = self.read_start_time(file_name_str) {
+                current_champion = Some(Champion {
+                    pid,
+                    score,
+                    rss,
+                    match_index,
+                    start_time: st,
+                    breakdown,
+                    swap,
+                    smaps,
+                    estimate: 0,
+                    accounting: KillAccounting::Rss.as_str(),
+                    // Name removed to avoid allocation
+                });
+            }
+        }
 
-                // A. Determine Match Priority (Read cmdline)
-                if self
-                    .read_file_into_buffer(file_name_str, "cmdline")
-                    .is_err()
-                {
-                    continue; // Process likely gone
-                }
+        // Post-Loop: If strategy was OOM Score, we might have 0 RSS in the champion.
+        // smaps_rollup walks every mapping, so outside `largestRss` it is only read for the winner.
+        if let Some(ref mut champ) = current_champion {
+            let pid_str = champ.pid.to_string();
+            if champ.rss == 0 {
+                champ.rss = self.read_rss(&pid_str);
+            }
+            if ctx.kill_accounting != KillAccounting::Rss && champ.smaps.is_none() {
+                champ.smaps = self.read_smaps_rollup(&pid_str);
+            }
+            (champ.estimate, champ.accounting) =
+                accounted(ctx.kill_accounting, champ.rss, champ.smaps.as_ref());
+            // Killing it gives back the swap it occupies, whatever its RSS
+            if relief == Relief::Swap {
+                let swap = match champ.swap {
+                    Some(swap) => swap,
+                    None => self.read_kb_field(&pid_str, "status", b"VmSwap"),
+                };
+                champ.swap = Some(swap);
+                (champ.estimate, champ.accounting) = (swap, "swap");
+            }
+        }
 
-                // Replace nulls with spaces
-                for b in self.read_buffer.iter_mut() {
-                    if *b == 0 {
-                        *b = 32;
-                    }
-                }
+        current_champion
+    }
 
-                // Cow::Borrowed if UTF-8, Owned if not.
-                let cmdline_cow = String::from_utf8_lossy(&self.read_buffer);
+    /// Ranks every process we could signal in selection order (match index, then score),
+    /// with ignoreNames hits listed last, without killing anything.
+    /// Allocates freely: meant for inspection, not for the OOM path.
+    pub fn list_candidates(&mut self, ctx: &RuntimeContext) -> Vec<Candidate> {
+        let current_uid = Uid::effective();
+        let mut candidates = Vec::new();
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return candidates;
+        };
 
-                // Check Ignored
-                let mut ignored = false;
-                for pat in &ctx.ignore_names_regex {
-                    if pat.matches(&cmdline_cow) {
-                        ignored = true;
-                        break;
-                    }
-                }
-                if ignored {
-                    continue;
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(pid_str) = file_name.to_str() else {
+                continue;
+            };
+            let Some(c) = self.classify(&entry, pid_str, ctx, None, current_uid) else {
+                continue;
+            };
+            let Scored {
+                score,
+                smaps,
+                breakdown,
+                ..
+            } = self.read_score(pid_str, ctx, c.match_index, Relief::Memory);
+            let rss = self.read_rss(pid_str);
+            let smaps = match smaps {
+                None if ctx.kill_accounting != KillAccounting::Rss => {
+                    self.read_smaps_rollup(pid_str)
                 }
+                smaps => smaps,
+            };
+            let (estimate, _) = accounted(ctx.kill_accounting, rss, smaps.as_ref());
+            let match_index = (c.match_index != usize::MAX).then_some(c.match_index);
+            candidates.push(Candidate {
+                pid: c.pid,
+                process_name: self
+                    .get_process_name(c.pid)
+                    .unwrap_or_else(|| "unknown".to_string()),
+                match_index,
+                target_pattern: match_index
+                    .and_then(|i| ctx.source.kill_targets.get(i))
+                    .map(|t| t.matcher().describe()),
+                ignored_by: c
+                    .ignored_by
+                    .and_then(|i| ctx.source.ignore_names.get(i))
+                    .map(|n| n.matcher().describe()),
+                score,
+                rss,
+                estimate,
+                smaps_rollup: smaps,
+                breakdown,
+            });
+        }
 
-                // Calculate Match Index
-                let mut match_index = usize::MAX;
-                for (idx, pat) in ctx.kill_targets_regex.iter().enumerate() {
-                    if pat.matches(&cmdline_cow) {
-                        match_index = idx;
-                        break;
-                    }
-                }
+        candidates.sort_by(selection_order);
+        candidates
+    }
 
-                // Check vs Current Champion (Optimization)
-                if let Some(champ) = &current_champion {
-                    if match_index > champ.match_index {
-                        continue;
-                    }
-                }
+    /// The most recent kill (KillExecuted event) and when it happened.
+    pub fn last_kill(&self) -> Option<&(DateTime<Utc>, SentinelEvent)> {
+        self.last_kill.as_ref()
+    }
 
-                // B. Calculate Score & RSS
-                let mut rss = 0;
-                let mut score = 0;
-
-                match ctx.kill_strategy {
-                    KillStrategy::LargestRss => {
-                        // Read statm for RSS
-                        if self.read_file_into_buffer(file_name_str, "statm").is_ok() {
-                            // format: total resident share ...
-                            if let Ok(s) = std::str::from_utf8(&self.read_buffer) {
-                                let mut parts = s.split_whitespace();
-                                if let Some(_total) = parts.next() {
-                                    if let Some(res) = parts.next() {
-                                        if let Ok(pages) = res.parse::<u64>() {
-                                            rss = pages * self.page_size;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        score = rss;
-                    }
-                    KillStrategy::HighestOomScore => {
-                        // Read oom_score
-                        if self
-                            .read_file_into_buffer(file_name_str, "oom_score")
-                            .is_ok()
-                        {
-                            if let Ok(s) = std::str::from_utf8(&self.read_buffer) {
-                                if let Ok(val) = s.trim().parse::<i32>() {
-                                    score = val as u64;
-                                }
-                            }
-                        }
-                    }
-                }
+    fn report_kill(&mut self, event: SentinelEvent) {
+        logging::emit(&event);
+        self.last_kill = Some((Utc::now(), event));
+    }
 
-                // Final Comparison
-                if let Some(champ) = &current_champion {
-                    if match_index == champ.match_index {
-                        if score <= champ.score {
-                            continue;
-                        }
-                    } else if match_index > champ.match_index {
-                        continue;
-                    }
-                }
+    /// Applies the eligibility filters (self, suspended, ownership, scope) to one /proc entry
+    /// and matches it against ignoreNames and killTargets.
+    /// Returns None for processes we may not touch at all.
+    fn classify(
+        &mut self,
+        entry: &fs::DirEntry,
+        pid_str: &str,
+        ctx: &RuntimeContext,
+        trigger_scope: Option<&ScopePattern>,
+        current_uid: Uid,
+    ) -> Option<Classified> {
+        // Filter 1: Must be PID (numeric)
+        let pid: u32 = pid_str.parse().ok()?;
 
-                // C. Become the Champion (Read stat for Start Time)
-                if self.read_file_into_buffer(file_name_str, "stat").is_ok() {
-                    if let Ok(s) = std::str::from_utf8(&self.read_buffer) {
-                        // Robust parsing: "pid (comm) state ppid ..."
-                        // Use split_once on ") " to correctly handle ')' in comm
-                        if let Some((_before, after_comm)) = s.split_once(") ") {
-                            // fields in after_comm:
-                            // 0:state ... 19:starttime (index 19 in this slice? No, count carefully)
-                            // Global stat fields:
-                            // 1: pid, 2: comm, 3: state, ..., 22: starttime
-                            // after_comm starts at field 3 (state).
-                            // So index 0 = field 3.
-                            // We want field 22.
-                            // Offset = 22 - 3 = 19.
-                            // So .nth(19) is correct.
-
-                            if let Some(start_time_str) = after_comm.split_whitespace().nth(19) {
-                                if let Ok(st) = start_time_str.parse::<u64>() {
-                                    current_champion = Some(Champion {
-                                        pid,
-                                        score,
-                                        rss,
-                                        match_index,
-                                        start_time: st,
-                                        // Name removed to avoid allocation
-                                    });
-                                }
-                            }
-                        }
-                    }
+        // Filter 2: Never kill self, nor targets we already suspended
+        if pid == std::process::id() || self.suspended.iter().any(|s| s.pid == pid) {
+            return None;
+        }
+
+        // Filter 3: Ownership Check (if not root)
+        if !current_uid.is_root() {
+            use std::os::unix::fs::MetadataExt;
+            // Avoid stat call if possible, but we need UID. entry.metadata() is cached from readdir? No, usually distinct.
+            if let Ok(meta) = entry.metadata() {
+                if meta.uid() != current_uid.as_raw() {
+                    return None;
                 }
+            } else {
+                return None;
             }
         }
 
-        // Post-Loop: If strategy was OOM Score, we might have 0 RSS in the champion.
-        if let Some(ref mut champ) = current_champion {
-            if champ.rss == 0 {
-                if self
-                    .read_file_into_buffer(&champ.pid.to_string(), "statm")
-                    .is_ok()
-                {
-                    if let Ok(s) = std::str::from_utf8(&self.read_buffer) {
-                        let mut parts = s.split_whitespace();
-                        if let Some(_total) = parts.next() {
-                            if let Some(res) = parts.next() {
-                                if let Ok(pages) = res.parse::<u64>() {
-                                    champ.rss = pages * self.page_size;
-                                }
-                            }
-                        }
-                    }
+        // Filter 4: cgroup Scope (killScope, the triggering cgroup, cgroups we froze)
+        let has_frozen = self
+            .suspended
+            .iter()
+            .any(|s| matches!(s.hold, Hold::Frozen { .. }));
+        if (!ctx.kill_scope.is_empty() || trigger_scope.is_some() || has_frozen)
+            && !self.in_scope(pid_str, ctx, trigger_scope, true)
+        {
+            return None;
+        }
+
+        // Filter 5: Holds memory. Zombies and kernel threads have an empty cmdline and nothing to
+        // free, whatever the strategy: a zombie matching an earlier killTargets entry outranks any
+        // live process regardless of score, and `weighted` still gives it age and target points.
+        // An unreaped victim would otherwise be picked again right after its kill.
+        let cmdline_len = self.read_file_into_buffer(pid_str, "cmdline").ok()?; // Process likely gone
+        if cmdline_len == 0 {
+            return None;
+        }
+
+        // Determine Match Priority: replace nulls in the cmdline read above with spaces
+        for b in self.read_buffer.iter_mut() {
+            if *b == 0 {
+                *b = 32;
+            }
+        }
+
+        // Cow::Borrowed if UTF-8, Owned if not.
+        let cmdline_cow = String::from_utf8_lossy(&self.read_buffer);
+
+        // Check Ignored
+        self.facts.reset(pid);
+        let ignored_by = ctx
+            .ignore_names
+            .iter()
+            .position(|m| m.matches(&cmdline_cow, &mut self.facts));
+
+        // Calculate Match Index
+        let match_index = ctx
+            .kill_targets
+            .iter()
+            .position(|target| target.matcher.matches(&cmdline_cow, &mut self.facts))
+            .unwrap_or(usize::MAX);
+
+        Some(Classified {
+            pid,
+            match_index,
+            ignored_by,
+        })
+    }
+
+    /// Scores one process for the strategy. RSS is left at 0 and smaps_rollup unread when the
+    /// strategy doesn't need them; the breakdown is only there for `weighted`.
+    /// `largestRss` ranks by the killAccounting figure (RSS, PSS or USS).
+    /// For a swap-triggered kill, `largestRss` and `highestOomScore` rank by VmSwap instead,
+    /// and `weighted` counts swap with weight 1 unless `killWeights.swap` is set.
+    fn read_score(
+        &mut self,
+        pid_str: &str,
+        ctx: &RuntimeContext,
+        match_index: usize,
+        relief: Relief,
+    ) -> Scored {
+        if relief == Relief::Swap && ctx.kill_strategy != KillStrategy::Weighted {
+            let swap = self.read_kb_field(pid_str, "status", b"VmSwap");
+            return Scored {
+                score: swap,
+                rss: 0,
+                swap: Some(swap),
+                smaps: None,
+                breakdown: None,
+            };
+        }
+        match ctx.kill_strategy {
+            KillStrategy::LargestRss => {
+                let rss = self.read_rss(pid_str);
+                let smaps = match ctx.kill_accounting {
+                    KillAccounting::Rss => None,
+                    _ => self.read_smaps_rollup(pid_str),
+                };
+                Scored {
+                    score: accounted(ctx.kill_accounting, rss, smaps.as_ref()).0,
+                    rss,
+                    swap: None,
+                    smaps,
+                    breakdown: None,
                 }
             }
+            KillStrategy::HighestOomScore => Scored {
+                score: self.read_oom_score(pid_str),
+                rss: 0,
+                swap: None,
+                smaps: None,
+                breakdown: None,
+            },
+            KillStrategy::Weighted => {
+                let mut weights = ctx.kill_weights;
+                if relief == Relief::Swap && weights.swap == 0.0 {
+                    weights.swap = 1.0;
+                }
+                let target = (match_index, ctx.kill_targets.len());
+                self.read_weighted(pid_str, &weights, target)
+            }
+        }
+    }
+
+    /// Scores one process for the `weighted` strategy, reading only factors with a weight.
+    /// `target` is (match index, number of killTargets).
+    fn read_weighted(
+        &mut self,
+        pid_str: &str,
+        weights: &KillWeights,
+        (match_index, target_count): (usize, usize),
+    ) -> Scored {
+        let total_memory = get_total_memory().max(1) as f64;
+        let points = |bytes: u64| bytes as f64 / total_memory * 1000.0;
+        let mut b = ScoreBreakdown::default();
+        let mut rss = 0;
+        let mut swap = None;
+        let mut smaps = None;
+
+        if weights.rss != 0.0 {
+            rss = self.read_rss(pid_str);
+            b.rss = weights.rss * points(rss);
+        }
+        if weights.pss != 0.0 {
+            smaps = self.read_smaps_rollup(pid_str);
+            b.pss = weights.pss * points(smaps.map_or(0, |s| s.pss));
+        }
+        if weights.swap != 0.0 {
+            let vm_swap = self.read_kb_field(pid_str, "status", b"VmSwap");
+            b.swap = weights.swap * points(vm_swap);
+            swap = Some(vm_swap);
+        }
+        if weights.oom_score != 0.0 {
+            b.oom_score = weights.oom_score * self.read_oom_score(pid_str) as f64;
+        }
+        if (weights.age != 0.0 || weights.cpu_idle != 0.0)
+            && let Some((cpu, start_time)) = self.read_cpu_time(pid_str)
+            && let Some(uptime) = uptime_ticks(self.clock_ticks)
+        {
+            let age = uptime.saturating_sub(start_time) as f64;
+            b.age = weights.age * age / (uptime.max(1) as f64) * 1000.0;
+            // Threads running in parallel can exceed wall time
+            let busy = if age > 0.0 { cpu as f64 / age } else { 1.0 };
+            b.cpu_idle = weights.cpu_idle * (1.0 - busy).clamp(0.0, 1.0) * 1000.0;
+        }
+        if match_index < target_count {
+            let rank = (target_count - match_index) as f64 / target_count as f64;
+            b.target_index = weights.target_index * rank * 1000.0;
         }
 
-        current_champion
+        b.total = b.rss + b.pss + b.swap + b.oom_score + b.age + b.cpu_idle + b.target_index;
+        Scored {
+            score: b.total.max(0.0) as u64,
+            rss,
+            swap,
+            smaps,
+            breakdown: Some(b),
+        }
+    }
+
+    /// None if smaps_rollup is missing (Linux < 4.14) or unreadable, e.g. for kernel threads.
+    fn read_smaps_rollup(&mut self, pid_str: &str) -> Option<SmapsRollup> {
+        self.read_file_into_buffer(pid_str, "smaps_rollup").ok()?;
+        let content = &self.read_buffer[..];
+        let private = kb_field(content, b"Private_Clean")? + kb_field(content, b"Private_Dirty")?;
+        Some(SmapsRollup {
+            pss: kb_field(content, b"Pss")?,
+            pss_anon: kb_field(content, b"Pss_Anon"),
+            pss_shmem: kb_field(content, b"Pss_Shmem"),
+            uss: private,
+            swap: kb_field(content, b"Swap").unwrap_or(0),
+        })
+    }
+
+    // A "Key: N kB" field of a /proc/[pid] file, in bytes (0 if missing)
+    fn read_kb_field(&mut self, pid_str: &str, file: &str, key: &[u8]) -> u64 {
+        if self.read_file_into_buffer(pid_str, file).is_err() {
+            return 0;
+        }
+        kb_field(&self.read_buffer, key).unwrap_or(0)
+    }
+
+    /// (utime + stime, starttime) from /proc/[pid]/stat, in clock ticks.
+    fn read_cpu_time(&mut self, pid_str: &str) -> Option<(u64, u64)> {
+        self.read_stat(pid_str)
+            .map(|stat| (stat.cpu_time, stat.start_time))
+    }
+
+    /// Resident set size in bytes from /proc/[pid]/statm (format: total resident share ...)
+    fn read_rss(&mut self, pid_str: &str) -> u64 {
+        if self.read_file_into_buffer(pid_str, "statm").is_ok()
+            && let Ok(s) = std::str::from_utf8(&self.read_buffer)
+            && let Some(res) = s.split_whitespace().nth(1)
+            && let Ok(pages) = res.parse::<u64>()
+        {
+            return pages * self.page_size;
+        }
+        0
+    }
+
+    fn read_oom_score(&mut self, pid_str: &str) -> u64 {
+        if self.read_file_into_buffer(pid_str, "oom_score").is_ok()
+            && let Ok(s) = std::str::from_utf8(&self.read_buffer)
+            && let Ok(val) = s.trim().parse::<i32>()
+        {
+            return val as u64;
+        }
+        0
     }
 
     fn read_file_into_buffer(&mut self, pid_str: &str, file: &str) -> std::io::Result<usize> {
@@ -340,17 +778,151 @@ impl Killer {
         Ok(bytes_read)
     }
 
-    fn kill_process(&mut self, ctx: &RuntimeContext, victim: &Champion, name: &str) -> Option<u64> {
-        let nix_pid = NixPid::from_raw(victim.pid as i32);
+    /// Checks the process's cgroup v2 path against the scope patterns and, with `skip_frozen`,
+    /// the cgroups we froze. Processes without a unified-hierarchy entry (cgroup v1 only) are
+    /// out of scope.
+    fn in_scope(
+        &mut self,
+        pid_str: &str,
+        ctx: &RuntimeContext,
+        trigger_scope: Option<&ScopePattern>,
+        skip_frozen: bool,
+    ) -> bool {
+        if self.read_file_into_buffer(pid_str, "cgroup").is_err() {
+            return false;
+        }
+        let Some(path) = unified_cgroup_path(&self.read_buffer) else {
+            return false;
+        };
+        (ctx.kill_scope.is_empty() || ctx.kill_scope.iter().any(|s| s.matches(path)))
+            && trigger_scope.is_none_or(|s| s.matches(path))
+            && !(skip_frozen
+                && self.suspended.iter().any(|s| match &s.hold {
+                    Hold::Frozen { scope, .. } => scope.matches(path),
+                    Hold::Stopped(_) => false,
+                }))
+    }
+
+    /// Reads field 22 (starttime) of /proc/[pid]/stat.
+    fn read_start_time(&mut self, pid_str: &str) -> Option<u64> {
+        self.read_stat(pid_str).map(|stat| stat.start_time)
+    }
+
+    fn read_stat(&mut self, pid_str: &str) -> Option<ProcStat<'_>> {
+        self.read_file_into_buffer(pid_str, "stat").ok()?;
+        parse_stat(std::str::from_utf8(&self.read_buffer).ok()?)
+    }
+
+    /// Pins the victim's identity right after selection.
+    /// Returns Ok(Some(pidfd)) once the pidfd is verified to refer to the champion,
+    /// Ok(None) if pidfd is unavailable (fall back to raw PID + start-time checks),
+    /// or Err(reason) if the champion is already gone.
+    fn pin_victim(&mut self, victim: &Champion) -> Result<Option<PidFd>, &'static str> {
+        let pidfd = match PidFd::open(victim.pid) {
+            Ok(fd) => fd,
+            Err(Errno::ESRCH) => return Err("ESRCH (Already gone)"),
+            Err(_) => return Ok(None),
+        };
+
+        // The pidfd now refers to whatever process owns the PID. Verify once that it is
+        // still our champion; from here on, the kernel guarantees identity for us.
+        match self.read_start_time(&victim.pid.to_string()) {
+            Some(st) if st == victim.start_time => Ok(Some(pidfd)),
+            Some(_) => Err("PID Reuse detected before SIGTERM"),
+            None => Err("Exited before SIGTERM"),
+        }
+    }
+
+    fn send_signal(pid: u32, pidfd: Option<&PidFd>, signal: Signal) -> nix::Result<()> {
+        if let Some(fd) = pidfd {
+            match fd.send_signal(signal) {
+                // pidfd_open worked but pidfd_send_signal is filtered (e.g. seccomp)
+                Err(Errno::ENOSYS) => {}
+                res => return res,
+            }
+        }
+        kill(NixPid::from_raw(pid as i32), signal)
+    }
+
+    /// Event-driven wait: returns as soon as the victim is gone.
+    /// Without a pidfd, falls back to polling /proc every EXIT_POLL_INTERVAL_MS; there a zombie
+    /// counts as exited, as it has already released its memory and only awaits reaping.
+    fn wait_for_exit(
+        &mut self,
+        victim: &Champion,
+        pidfd: Option<&PidFd>,
+        timeout: Duration,
+    ) -> WaitOutcome {
+        let started = Instant::now();
+
+        if let Some(fd) = pidfd {
+            return if fd.wait_exit(timeout) {
+                WaitOutcome::Exited(started.elapsed())
+            } else {
+                WaitOutcome::TimedOut
+            };
+        }
+
+        let pid_str = victim.pid.to_string();
+        loop {
+            match self.read_stat(&pid_str).map(|s| (s.start_time, s.state)) {
+                None | Some((_, 'Z' | 'X')) => return WaitOutcome::Exited(started.elapsed()),
+                Some((st, _)) if st != victim.start_time => return WaitOutcome::PidReused,
+                Some(_) => {}
+            }
+            let elapsed = started.elapsed();
+            if elapsed >= timeout {
+                return WaitOutcome::TimedOut;
+            }
+            thread::sleep((timeout - elapsed).min(Duration::from_millis(EXIT_POLL_INTERVAL_MS)));
+        }
+    }
+
+    /// Returns what counts as freed: the observed growth of the relieved pool once the victim
+    /// is gone (see `credited`), or 0 if it was gone before we signalled it. Targets with a
+    /// `freeze`/`stop` action are paused instead. None aborts the sequence.
+    fn kill_process(
+        &mut self,
+        ctx: &RuntimeContext,
+        victim: &Champion,
+        name: &str,
+        relief: Relief,
+    ) -> Option<Step> {
+        // 0. Pin Identity (pidfd), closing the window between selection and signalling
+        let pidfd = match self.pin_victim(victim) {
+            Ok(fd) => fd,
+            Err(reason) => {
+                logging::emit(&SentinelEvent::KillCandidateIgnored {
+                    pid: victim.pid,
+                    mechanism: MECHANISM_PIDFD.to_string(),
+                    reason: reason.to_string(),
+                });
+                return Some(Step::Freed(0));
+            }
+        };
+        let target = ctx.kill_targets.get(victim.match_index);
+        match target.map_or(TargetAction::Kill, |t| t.action) {
+            TargetAction::Kill => {}
+            action => return self.suspend(victim, pidfd, name, action),
+        }
+        let before = self.sample_free(relief);
+        if target.is_some_and(|t| t.kill_cgroup)
+            && let Some(freed) = self.kill_cgroup(victim, pidfd.as_ref(), name, (relief, before))
+        {
+            return Some(Step::Freed(freed));
+        }
+
+        let mechanism = mechanism_of(pidfd.as_ref());
 
         // 1. Send SIGTERM
-        if let Err(e) = kill(nix_pid, Signal::SIGTERM) {
-            if e == nix::errno::Errno::ESRCH {
+        if let Err(e) = Self::send_signal(victim.pid, pidfd.as_ref(), Signal::SIGTERM) {
+            if e == Errno::ESRCH {
                 logging::emit(&SentinelEvent::KillCandidateIgnored {
                     pid: victim.pid,
+                    mechanism: mechanism.to_string(),
                     reason: "ESRCH (Already gone)".to_string(),
                 });
-                return Some(victim.rss);
+                return Some(Step::Freed(0));
             }
             logging::emit(&SentinelEvent::KillSequenceAborted {
                 reason: format!("Failed to send SIGTERM to {}: {}", victim.pid, e),
@@ -358,53 +930,846 @@ impl Killer {
             return None;
         }
 
-        thread::sleep(Duration::from_millis(ctx.sigterm_wait_ms));
-
-        // 2. Verify Identity (PID Reuse Check)
-        if self
-            .read_file_into_buffer(&victim.pid.to_string(), "stat")
-            .is_ok()
-        {
-            if let Ok(s) = std::str::from_utf8(&self.read_buffer) {
-                if let Some((_before, after_comm)) = s.split_once(") ") {
-                    if let Some(start_time_str) = after_comm.split_whitespace().nth(19) {
-                        if let Ok(new_st) = start_time_str.parse::<u64>() {
-                            if new_st != victim.start_time {
-                                logging::emit(&SentinelEvent::KillCandidateIgnored {
-                                    pid: victim.pid,
-                                    reason: "PID Reuse detected during wait".to_string(),
-                                });
-                                return Some(victim.rss);
-                            }
-                        }
-                    }
-                }
+        // 2. Wait for exit (bounded by sigtermWaitMs), verifying identity on the fallback path
+        let timeout = Duration::from_millis(ctx.sigterm_wait_ms);
+        match self.wait_for_exit(victim, pidfd.as_ref(), timeout) {
+            WaitOutcome::Exited(elapsed) => {
+                let measured = self.observe_freed(relief, before);
+                self.report_kill(SentinelEvent::KillExecuted {
+                    pid: victim.pid,
+                    process_name: name.to_string(),
+                    strategy: "SIGTERM".to_string(),
+                    mechanism: mechanism.to_string(),
+                    rss_freed: victim.rss,
+                    estimated_freed: victim.estimate,
+                    accounting: victim.accounting.to_string(),
+                    observed_freed: measured.observed(),
+                    smaps_rollup: victim.smaps,
+                    time_to_exit_ms: Some(elapsed.as_millis() as u64),
+                    expedited_reclaim: None,
+                    cgroup: None,
+                });
+                return Some(Step::Freed(credited(measured, victim.estimate)));
             }
-        } else {
-            // Process GONE
-            logging::emit(&SentinelEvent::KillExecuted {
-                pid: victim.pid,
-                process_name: name.to_string(),
-                strategy: "SIGTERM".to_string(),
-                rss_freed: victim.rss,
-            });
-            return Some(victim.rss);
+            WaitOutcome::PidReused => {
+                logging::emit(&SentinelEvent::KillCandidateIgnored {
+                    pid: victim.pid,
+                    mechanism: mechanism.to_string(),
+                    reason: "PID Reuse detected during wait".to_string(),
+                });
+                return Some(Step::Freed(0));
+            }
+            WaitOutcome::TimedOut => {}
         }
 
+        self.sigkill(victim, pidfd.as_ref(), name, mechanism, (relief, before))
+            .map(Step::Freed)
+    }
+
+    // Steps 3 and 4 of `kill_process`, also used to kill a paused target outright
+    fn sigkill(
+        &mut self,
+        victim: &Champion,
+        pidfd: Option<&PidFd>,
+        name: &str,
+        mechanism: &str,
+        (relief, before): (Relief, Option<u64>),
+    ) -> Option<u64> {
         // 3. SIGKILL
-        if let Err(e) = kill(nix_pid, Signal::SIGKILL) {
+        if let Err(e) = Self::send_signal(victim.pid, pidfd, Signal::SIGKILL) {
             logging::emit(&SentinelEvent::KillSequenceAborted {
                 reason: format!("Failed to send SIGKILL to {}: {}", victim.pid, e),
             });
             return None;
         }
 
-        logging::emit(&SentinelEvent::KillExecuted {
+        // 4. Expedited Reclaim: don't wait for a thrashing victim to unmap itself
+        let expedited_reclaim = pidfd.map(|fd| match fd.mrelease() {
+            Ok(()) => true,
+            Err(e) => {
+                logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Debug,
+                    text: format!("process_mrelease failed for PID {}: {}", victim.pid, e),
+                });
+                false
+            }
+        });
+        let measured = self.settle_and_observe(victim, pidfd, relief, before);
+
+        self.report_kill(SentinelEvent::KillExecuted {
             pid: victim.pid,
             process_name: name.to_string(),
             strategy: "SIGKILL".to_string(),
+            mechanism: mechanism.to_string(),
             rss_freed: victim.rss,
+            estimated_freed: victim.estimate,
+            accounting: victim.accounting.to_string(),
+            observed_freed: measured.observed(),
+            smaps_rollup: victim.smaps,
+            time_to_exit_ms: None,
+            expedited_reclaim,
+            cgroup: None,
+        });
+        Some(credited(measured, victim.estimate))
+    }
+
+    /// Free bytes in the relieved pool right now. None if /proc/meminfo can't be read.
+    fn sample_free(&mut self, relief: Relief) -> Option<u64> {
+        let info = self.meminfo_reader.as_mut()?.read().ok()?;
+        if relief == Relief::Memory {
+            return Some(info.mem_available);
+        }
+        let swap_free = self
+            .swap_reader
+            .as_mut()
+            .and_then(|r| r.read(&info).ok())
+            .map_or(info.swap_free, |usage| usage.free);
+        Some(match relief {
+            Relief::Swap => swap_free,
+            _ => info.mem_available + swap_free,
+        })
+    }
+
+    // How much the relieved pool grew since `before`. Whatever else ran meanwhile is included;
+    // a pool that shrank counts as nothing freed.
+    fn observe_freed(&mut self, relief: Relief, before: Option<u64>) -> Measured {
+        match (before, self.sample_free(relief)) {
+            (Some(before), Some(after)) => Measured::Freed(after.saturating_sub(before)),
+            _ => Measured::Unreadable,
+        }
+    }
+
+    // After SIGKILL: waits (bounded) for the victim to be gone, then measures.
+    // A victim still around, e.g. stuck in uninterruptible sleep, isn't measured.
+    fn settle_and_observe(
+        &mut self,
+        victim: &Champion,
+        pidfd: Option<&PidFd>,
+        relief: Relief,
+        before: Option<u64>,
+    ) -> Measured {
+        let timeout = Duration::from_millis(SIGKILL_SETTLE_MS);
+        match self.wait_for_exit(victim, pidfd, timeout) {
+            WaitOutcome::Exited(_) | WaitOutcome::PidReused => self.observe_freed(relief, before),
+            WaitOutcome::TimedOut => Measured::NotGone,
+        }
+    }
+
+    /// Resolves the victim's cgroup v2 path for whole-cgroup actions (kill/freeze).
+    /// Only a leaf application unit qualifies (see `is_app_unit`): never a login session, a
+    /// slice, a cgroup with children, or any cgroup ram-sentinel itself runs in.
+    fn victim_cgroup(&mut self, victim: &Champion) -> Result<String, String> {
+        self.read_file_into_buffer(&victim.pid.to_string(), "cgroup")
+            .map_err(|e| format!("cannot read /proc/{}/cgroup: {}", victim.pid, e))?;
+        let path = unified_cgroup_path(&self.read_buffer)
+            .ok_or("no cgroup v2 membership")?
+            .to_string();
+
+        if !is_app_unit(&path) {
+            return Err(format!("{} is not an app scope or service", path));
+        }
+        let has_children = fs::read_dir(cgroup_dir(&path))
+            .map_err(|e| format!("cannot read {}: {}", path, e))?
+            .flatten()
+            .any(|entry| entry.file_type().is_ok_and(|t| t.is_dir()));
+        if has_children {
+            return Err(format!("{} has child cgroups", path));
+        }
+        let own = fs::read("/proc/self/cgroup").unwrap_or_default();
+        if let Some(own_path) = unified_cgroup_path(&own)
+            && ScopePattern::for_cgroup(&path).matches(own_path)
+        {
+            return Err(format!("ram-sentinel itself runs inside {}", path));
+        }
+        Ok(path)
+    }
+
+    /// Kills every process in the victim's cgroup at once via `cgroup.kill` (Linux 5.14+),
+    /// so helpers of a multi-process app go down together.
+    /// Returns None if the cgroup cannot be killed safely; the caller then kills the single PID.
+    /// Takes the relieved pool and its free bytes before the kill, to measure what it freed.
+    fn kill_cgroup(
+        &mut self,
+        victim: &Champion,
+        pidfd: Option<&PidFd>,
+        name: &str,
+        (relief, before): (Relief, Option<u64>),
+    ) -> Option<u64> {
+        let fall_back = |reason: String| {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Warn,
+                text: format!(
+                    "Cannot kill cgroup of PID {}: {}. Killing the process only.",
+                    victim.pid, reason
+                ),
+            });
+            None
+        };
+
+        let path = match self.victim_cgroup(victim) {
+            Ok(path) => path,
+            Err(reason) => return fall_back(reason),
+        };
+
+        // Estimate from the whole cgroup's charge rather than the champion's own size
+        let dir = cgroup_dir(&path);
+        let files = MemoryFiles::open(&dir).ok();
+        let current = files
+            .as_ref()
+            .and_then(|f| f.read_usage().ok())
+            .map(|(current, _)| current);
+        let swap = files.as_ref().and_then(|f| f.read_swap().ok());
+        let (freed, accounting) =
+            cgroup_estimate(relief, current, swap).unwrap_or((victim.estimate, victim.accounting));
+
+        if let Err(e) = fs::write(dir.join("cgroup.kill"), "1") {
+            return fall_back(format!("writing {}/cgroup.kill failed: {}", path, e));
+        }
+
+        // The champion is SIGKILLed along with the rest; reap its memory early as usual
+        let expedited_reclaim = pidfd.map(|fd| fd.mrelease().is_ok());
+        // Only the champion is waited for; the rest of the cgroup dies alongside it
+        let measured = self.settle_and_observe(victim, pidfd, relief, before);
+
+        self.report_kill(SentinelEvent::KillExecuted {
+            pid: victim.pid,
+            process_name: name.to_string(),
+            strategy: "SIGKILL".to_string(),
+            mechanism: MECHANISM_CGROUP_KILL.to_string(),
+            rss_freed: current.unwrap_or(victim.rss),
+            estimated_freed: freed,
+            accounting: accounting.to_string(),
+            observed_freed: measured.observed(),
+            smaps_rollup: victim.smaps,
+            time_to_exit_ms: None,
+            expedited_reclaim,
+            cgroup: Some(path),
+        });
+        Some(credited(measured, freed))
+    }
+
+    /// Pauses the victim instead of killing it: freezes its cgroup (`cgroup.freeze`), falling
+    /// back to SIGSTOP on the single process. A paused task stops allocating but keeps its
+    /// memory, so this ends the sequence; if memory runs short again while it is still paused,
+    /// the next sequence kills it (see `escalate`).
+    fn suspend(
+        &mut self,
+        victim: &Champion,
+        pidfd: Option<PidFd>,
+        name: &str,
+        action: TargetAction,
+    ) -> Option<Step> {
+        if action == TargetAction::Freeze {
+            let frozen = self.victim_cgroup(victim).and_then(|path| {
+                fs::write(cgroup_dir(&path).join("cgroup.freeze"), "1")
+                    .map(|()| path.clone())
+                    .map_err(|e| format!("writing {}/cgroup.freeze failed: {}", path, e))
+            });
+            match frozen {
+                Ok(path) => {
+                    logging::emit(&SentinelEvent::ProcessFrozen {
+                        pid: victim.pid,
+                        process_name: name.to_string(),
+                        action: TargetAction::Freeze.as_str().to_string(),
+                        cgroup: Some(path.clone()),
+                        rss: victim.rss,
+                    });
+                    self.suspended.push(Suspended {
+                        pid: victim.pid,
+                        process_name: name.to_string(),
+                        start_time: victim.start_time,
+                        since: Instant::now(),
+                        hold: Hold::Frozen {
+                            scope: ScopePattern::for_cgroup(&path),
+                            cgroup: path,
+                        },
+                    });
+                    return Some(Step::Suspended);
+                }
+                Err(reason) => logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Warn,
+                    text: format!(
+                        "Cannot freeze cgroup of PID {}: {}. Stopping the process only.",
+                        victim.pid, reason
+                    ),
+                }),
+            }
+        }
+
+        match Self::send_signal(victim.pid, pidfd.as_ref(), Signal::SIGSTOP) {
+            Ok(()) => {
+                logging::emit(&SentinelEvent::ProcessFrozen {
+                    pid: victim.pid,
+                    process_name: name.to_string(),
+                    action: TargetAction::Stop.as_str().to_string(),
+                    cgroup: None,
+                    rss: victim.rss,
+                });
+                self.suspended.push(Suspended {
+                    pid: victim.pid,
+                    process_name: name.to_string(),
+                    start_time: victim.start_time,
+                    since: Instant::now(),
+                    hold: Hold::Stopped(pidfd),
+                });
+                Some(Step::Suspended)
+            }
+            Err(Errno::ESRCH) => {
+                logging::emit(&SentinelEvent::KillCandidateIgnored {
+                    pid: victim.pid,
+                    mechanism: mechanism_of(pidfd.as_ref()).to_string(),
+                    reason: "ESRCH (Already gone)".to_string(),
+                });
+                Some(Step::Freed(0))
+            }
+            Err(e) => {
+                logging::emit(&SentinelEvent::KillSequenceAborted {
+                    reason: format!("Failed to send SIGSTOP to {}: {}", victim.pid, e),
+                });
+                None
+            }
+        }
+    }
+
+    /// Index of the oldest paused target within this sequence's scope, if any.
+    fn oldest_paused(
+        &mut self,
+        ctx: &RuntimeContext,
+        trigger_scope: Option<&ScopePattern>,
+    ) -> Option<usize> {
+        let scoped = !ctx.kill_scope.is_empty() || trigger_scope.is_some();
+        (0..self.suspended.len()).find(|&i| {
+            let pid_str = self.suspended[i].pid.to_string();
+            !scoped || self.in_scope(&pid_str, ctx, trigger_scope, false)
+        })
+    }
+
+    /// Removes the oldest paused target within this sequence's scope once it has been paused
+    /// for `pauseGraceMs`. Targets are paused in order, so no younger one can be due before it.
+    fn take_overdue(
+        &mut self,
+        ctx: &RuntimeContext,
+        trigger_scope: Option<&ScopePattern>,
+    ) -> Option<Suspended> {
+        let index = self.oldest_paused(ctx, trigger_scope)?;
+        let grace = Duration::from_millis(ctx.pause_grace_ms);
+        (self.suspended[index].since.elapsed() >= grace).then(|| self.suspended.remove(index))
+    }
+
+    /// Kills a target paused by an earlier sequence: pausing it didn't keep memory from running
+    /// short again. Goes straight to SIGKILL, as a stopped process can't act on SIGTERM, and
+    /// kills the whole cgroup if that is what was frozen.
+    fn escalate(&mut self, paused: Suspended, relief: Relief) -> Option<Step> {
+        let Suspended {
+            pid,
+            process_name: name,
+            start_time,
+            since,
+            hold,
+        } = paused;
+        let pid_str = pid.to_string();
+        let rss = self.read_rss(&pid_str);
+        let (estimate, accounting) = match relief {
+            Relief::Swap => (self.read_kb_field(&pid_str, "status", b"VmSwap"), "swap"),
+            _ => (rss, KillAccounting::Rss.as_str()),
+        };
+        let victim = Champion {
+            pid,
+            score: 0,
+            rss,
+            match_index: usize::MAX,
+            start_time,
+            breakdown: None,
+            swap: None,
+            smaps: None,
+            estimate,
+            accounting,
+        };
+        let (frozen, pidfd) = match hold {
+            Hold::Frozen { cgroup, .. } => (Some(cgroup), None),
+            Hold::Stopped(pidfd) => (None, pidfd),
+        };
+        let thaw_rest = |cgroup: &str| fs::write(cgroup_dir(cgroup).join("cgroup.freeze"), "0");
+
+        let paused_by = mechanism_of(pidfd.as_ref());
+        let pinned = match pidfd {
+            Some(fd) if fd.wait_exit(Duration::ZERO) => Err("Exited while paused"),
+            Some(fd) => Ok(Some(fd)),
+            None => self.pin_victim(&victim),
+        };
+        let pidfd = match pinned {
+            Ok(fd) => fd,
+            Err(reason) => {
+                logging::emit(&SentinelEvent::KillCandidateIgnored {
+                    pid,
+                    mechanism: paused_by.to_string(),
+                    reason: reason.to_string(),
+                });
+                if let Some(cgroup) = &frozen {
+                    let _ = thaw_rest(cgroup);
+                }
+                return Some(Step::Freed(0));
+            }
+        };
+        logging::emit(&SentinelEvent::Message {
+            level: LogLevel::Warn,
+            text: format!(
+                "PID {} {} was paused {}s ago and memory is short again. Killing it.",
+                pid,
+                name,
+                since.elapsed().as_secs()
+            ),
         });
-        Some(victim.rss)
+
+        let before = self.sample_free(relief);
+        if let Some(cgroup) = &frozen {
+            // cgroup.kill takes frozen tasks down as well
+            if let Some(freed) = self.kill_cgroup(&victim, pidfd.as_ref(), &name, (relief, before))
+            {
+                return Some(Step::Freed(freed));
+            }
+            // Only the process is killed below; the rest of its cgroup may run again
+            let _ = thaw_rest(cgroup);
+        }
+        let mechanism = mechanism_of(pidfd.as_ref());
+        self.sigkill(&victim, pidfd.as_ref(), &name, mechanism, (relief, before))
+            .map(Step::Freed)
+    }
+
+    pub fn has_suspended(&self) -> bool {
+        !self.suspended.is_empty()
+    }
+
+    pub fn suspended_count(&self) -> usize {
+        self.suspended.len()
+    }
+
+    /// Resumes the most recently suspended target (the least expendable one).
+    pub fn thaw_latest(&mut self) {
+        if let Some(s) = self.suspended.pop() {
+            self.thaw(s);
+        }
+    }
+
+    /// Resumes everything, e.g. on shutdown, so nothing stays paused without a sentinel.
+    pub fn thaw_all(&mut self) {
+        while let Some(s) = self.suspended.pop() {
+            self.thaw(s);
+        }
+    }
+
+    fn thaw(&mut self, s: Suspended) {
+        let result = match &s.hold {
+            Hold::Frozen { cgroup, .. } => {
+                fs::write(cgroup_dir(cgroup).join("cgroup.freeze"), "0").map_err(|e| e.to_string())
+            }
+            Hold::Stopped(pidfd) => {
+                // Without a pidfd, make sure the PID still belongs to the process we stopped
+                if pidfd.is_none() && self.read_start_time(&s.pid.to_string()) != Some(s.start_time)
+                {
+                    return;
+                }
+                match Self::send_signal(s.pid, pidfd.as_ref(), Signal::SIGCONT) {
+                    Err(Errno::ESRCH) => return, // Exited (or was killed) while stopped
+                    res => res.map_err(|e| e.to_string()),
+                }
+            }
+        };
+
+        let (action, cgroup) = match s.hold {
+            Hold::Frozen { cgroup, .. } => (TargetAction::Freeze, Some(cgroup)),
+            Hold::Stopped(_) => (TargetAction::Stop, None),
+        };
+        match result {
+            Ok(()) => logging::emit(&SentinelEvent::ProcessThawed {
+                pid: s.pid,
+                process_name: s.process_name,
+                action: action.as_str().to_string(),
+                cgroup,
+                suspended_ms: s.since.elapsed().as_millis() as u64,
+            }),
+            Err(e) => logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Error,
+                text: format!("Failed to resume {} (PID {}): {}", s.process_name, s.pid, e),
+            }),
+        }
+    }
+}
+
+// What a kill counts for against the deficit: the measured relief; the estimate if the pool
+// can't be sampled at all; a fraction of it if the victim didn't exit in time
+fn credited(measured: Measured, estimate: u64) -> u64 {
+    match measured {
+        Measured::Freed(bytes) => bytes,
+        Measured::Unreadable => estimate,
+        Measured::NotGone => estimate / MIN_CREDIT_DIVISOR,
+    }
+}
+
+/// What killing a whole cgroup gives back to the `relief` pool, from its memory.current and
+/// memory.swap.current, and which figure that is. None if the needed file couldn't be read.
+fn cgroup_estimate(
+    relief: Relief,
+    current: Option<u64>,
+    swap: Option<u64>,
+) -> Option<(u64, &'static str)> {
+    match (relief, current, swap) {
+        (Relief::Memory, Some(current), _) => Some((current, "memory.current")),
+        (Relief::Swap, _, Some(swap)) => Some((swap, "memory.swap.current")),
+        (Relief::Combined, Some(current), Some(swap)) => {
+            Some((current + swap, "memory.current+memory.swap.current"))
+        }
+        // No swap accounting: nothing of it to count
+        (Relief::Combined, Some(current), None) => Some((current, "memory.current")),
+        _ => None,
+    }
+}
+
+/// The freed estimate for `accounting` and which figure it is. Falls back to RSS when
+/// smaps_rollup couldn't be read.
+fn accounted(
+    accounting: KillAccounting,
+    rss: u64,
+    smaps: Option<&SmapsRollup>,
+) -> (u64, &'static str) {
+    match (accounting, smaps) {
+        (KillAccounting::Pss, Some(s)) => (s.pss, accounting.as_str()),
+        (KillAccounting::Uss, Some(s)) => (s.uss, accounting.as_str()),
+        _ => (rss, KillAccounting::Rss.as_str()),
+    }
+}
+
+// How a signal reaches the process: through its pidfd, or by raw PID without one
+fn mechanism_of(pidfd: Option<&PidFd>) -> &'static str {
+    if pidfd.is_some() {
+        MECHANISM_PIDFD
+    } else {
+        MECHANISM_PID
+    }
+}
+
+// Time since boot in clock ticks, the clock /proc/[pid]/stat starttime counts in
+fn uptime_ticks(clock_ticks: u64) -> Option<u64> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // Safety: ts is a valid, writable timespec
+    if unsafe { libc::clock_gettime(libc::CLOCK_BOOTTIME, &mut ts) } != 0 {
+        return None;
+    }
+    Some(ts.tv_sec as u64 * clock_ticks + ts.tv_nsec as u64 * clock_ticks / 1_000_000_000)
+}
+
+// Ignored last. Weighted candidates by total; otherwise by killTargets position
+// (non-matches after matches), then highest score
+fn selection_order(a: &Candidate, b: &Candidate) -> std::cmp::Ordering {
+    let ignored = a.ignored_by.is_some().cmp(&b.ignored_by.is_some());
+    if let (Some(a), Some(b)) = (a.breakdown, b.breakdown) {
+        return ignored.then(b.total.total_cmp(&a.total));
+    }
+    ignored
+        .then(
+            a.match_index
+                .unwrap_or(usize::MAX)
+                .cmp(&b.match_index.unwrap_or(usize::MAX)),
+        )
+        .then(b.score.cmp(&a.score))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::Command;
+
+    fn champion(killer: &mut Killer, pid: u32) -> Champion {
+        Champion {
+            pid,
+            score: 0,
+            rss: 0,
+            match_index: 0,
+            start_time: killer.read_start_time(&pid.to_string()).unwrap(),
+            breakdown: None,
+            swap: None,
+            smaps: None,
+            estimate: 0,
+            accounting: KillAccounting::Rss.as_str(),
+        }
+    }
+
+    #[test]
+    fn pin_victim_verifies_start_time() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut killer = Killer::new();
+        let mut victim = champion(&mut killer, child.id());
+        assert!(matches!(killer.pin_victim(&victim), Ok(Some(_))));
+
+        victim.start_time += 1;
+        assert_eq!(
+            killer.pin_victim(&victim).err(),
+            Some("PID Reuse detected before SIGTERM")
+        );
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+        assert_eq!(
+            killer.pin_victim(&victim).err(),
+            Some("ESRCH (Already gone)")
+        );
+    }
+
+    #[test]
+    fn paused_targets_are_killed_oldest_first() {
+        let mut ctx = Config::sane_defaults().into_runtime(None).unwrap();
+        ctx.pause_grace_ms = 0;
+        let mut killer = Killer::new();
+        let mut children: Vec<_> = (0..2)
+            .map(|_| Command::new("sleep").arg("30").spawn().unwrap())
+            .collect();
+        for child in &children {
+            let victim = champion(&mut killer, child.id());
+            let pidfd = PidFd::open(victim.pid).ok();
+            let step = killer.suspend(&victim, pidfd, "sleep", TargetAction::Stop);
+            assert!(matches!(step, Some(Step::Suspended)));
+        }
+
+        let oldest = killer.take_overdue(&ctx, None).unwrap();
+        assert_eq!(oldest.pid, children[0].id());
+        assert!(matches!(
+            killer.escalate(oldest, Relief::Memory),
+            Some(Step::Freed(_))
+        ));
+        // SIGKILL while stopped, no SIGTERM first
+        assert_eq!(
+            children[0].wait().unwrap().signal(),
+            Some(Signal::SIGKILL as i32)
+        );
+        assert_eq!(killer.suspended_count(), 1);
+
+        killer.thaw_all();
+        children[1].kill().unwrap();
+        children[1].wait().unwrap();
+    }
+
+    #[test]
+    fn freshly_paused_targets_get_their_grace() {
+        let ctx = Config::sane_defaults().into_runtime(None).unwrap();
+        let mut killer = Killer::new();
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let victim = champion(&mut killer, child.id());
+        let pidfd = PidFd::open(victim.pid).ok();
+        killer.suspend(&victim, pidfd, "sleep", TargetAction::Stop);
+
+        assert!(killer.take_overdue(&ctx, None).is_none());
+        assert_eq!(killer.oldest_paused(&ctx, None), Some(0));
+        assert_eq!(killer.suspended_count(), 1);
+
+        killer.thaw_all();
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn zombies_are_never_candidates() {
+        let ctx = Config::sane_defaults().into_runtime(None).unwrap();
+        let mut killer = Killer::new();
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid_str = child.id().to_string();
+        let classify = |killer: &mut Killer| {
+            let entry = fs::read_dir("/proc")
+                .unwrap()
+                .flatten()
+                .find(|e| e.file_name().to_str() == Some(&pid_str))
+                .unwrap();
+            killer.classify(&entry, &pid_str, &ctx, None, Uid::effective())
+        };
+        // The child may still be between fork and exec at first
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while classify(&mut killer).is_none() {
+            assert!(
+                Instant::now() < deadline,
+                "live child never became a candidate"
+            );
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        child.kill().unwrap();
+        while killer.read_stat(&pid_str).map(|s| s.state) != Some('Z') {
+            assert!(Instant::now() < deadline, "child never became a zombie");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(classify(&mut killer).is_none());
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn fallback_wait_treats_zombies_as_exited() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut killer = Killer::new();
+        let victim = champion(&mut killer, child.id());
+        // Killed but not yet reaped: /proc/<pid> stays until wait()
+        child.kill().unwrap();
+        let outcome = killer.wait_for_exit(&victim, None, Duration::from_secs(10));
+        child.wait().unwrap();
+        assert!(matches!(outcome, WaitOutcome::Exited(t) if t < Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn pidfd_wait_ends_on_exit() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut killer = Killer::new();
+        let victim = champion(&mut killer, child.id());
+        let fd = PidFd::open(victim.pid).unwrap();
+        assert!(matches!(
+            killer.wait_for_exit(&victim, Some(&fd), Duration::from_millis(50)),
+            WaitOutcome::TimedOut
+        ));
+        child.kill().unwrap();
+        let outcome = killer.wait_for_exit(&victim, Some(&fd), Duration::from_secs(10));
+        child.wait().unwrap();
+        assert!(matches!(outcome, WaitOutcome::Exited(t) if t < Duration::from_secs(5)));
+    }
+
+    fn candidate(match_index: Option<usize>, ignored: bool, score: u64) -> Candidate {
+        Candidate {
+            pid: score as u32,
+            process_name: String::new(),
+            match_index,
+            target_pattern: None,
+            ignored_by: ignored.then(String::new),
+            score,
+            rss: 0,
+            estimate: 0,
+            smaps_rollup: None,
+            breakdown: None,
+        }
+    }
+
+    #[test]
+    fn candidates_listed_in_selection_order() {
+        let mut list = [
+            candidate(None, false, 90),
+            candidate(Some(1), false, 10),
+            candidate(Some(0), true, 99),
+            candidate(Some(1), false, 50),
+            candidate(Some(0), false, 1),
+        ];
+        list.sort_by(selection_order);
+        let order: Vec<u64> = list.iter().map(|c| c.score).collect();
+        assert_eq!(order, [1, 50, 10, 90, 99]);
+    }
+
+    #[test]
+    fn weighted_candidates_ranked_by_total() {
+        let weighted = |match_index, ignored, total| Candidate {
+            breakdown: Some(ScoreBreakdown {
+                total,
+                ..Default::default()
+            }),
+            ..candidate(match_index, ignored, total as u64)
+        };
+        // Match position doesn't count once scored, ignoreNames hits still come last
+        let mut list = [
+            weighted(Some(0), false, 300.0),
+            weighted(None, true, 900.0),
+            weighted(None, false, 500.0),
+            weighted(Some(1), false, 400.0),
+        ];
+        list.sort_by(selection_order);
+        let order: Vec<u64> = list.iter().map(|c| c.score).collect();
+        assert_eq!(order, [500, 400, 300, 900]);
+    }
+
+    #[test]
+    fn accounting_falls_back_to_rss() {
+        let smaps = SmapsRollup {
+            pss: 300,
+            pss_anon: None,
+            pss_shmem: None,
+            uss: 200,
+            swap: 0,
+        };
+        assert_eq!(
+            accounted(KillAccounting::Pss, 400, Some(&smaps)),
+            (300, "pss")
+        );
+        assert_eq!(
+            accounted(KillAccounting::Uss, 400, Some(&smaps)),
+            (200, "uss")
+        );
+        assert_eq!(
+            accounted(KillAccounting::Rss, 400, Some(&smaps)),
+            (400, "rss")
+        );
+        assert_eq!(accounted(KillAccounting::Uss, 400, None), (400, "rss"));
+    }
+
+    #[test]
+    fn cgroup_estimate_counts_the_relieved_pool() {
+        let (current, swap) = (Some(300), Some(200));
+        assert_eq!(
+            cgroup_estimate(Relief::Memory, current, swap),
+            Some((300, "memory.current"))
+        );
+        assert_eq!(
+            cgroup_estimate(Relief::Swap, current, swap),
+            Some((200, "memory.swap.current"))
+        );
+        assert_eq!(
+            cgroup_estimate(Relief::Combined, current, swap),
+            Some((500, "memory.current+memory.swap.current"))
+        );
+        assert_eq!(
+            cgroup_estimate(Relief::Combined, current, None),
+            Some((300, "memory.current"))
+        );
+        // Falls back to the champion's own estimate
+        assert_eq!(cgroup_estimate(Relief::Swap, current, None), None);
+    }
+
+    #[test]
+    fn swap_related_meminfo_fields_need_swap_relief() {
+        for field in [
+            MemInfoField::SwapCached,
+            MemInfoField::Zswap,
+            MemInfoField::Zswapped,
+        ] {
+            assert_eq!(Relief::for_meminfo(field), Relief::Swap);
+        }
+        assert_eq!(Relief::for_meminfo(MemInfoField::Shmem), Relief::Memory);
+    }
+
+    #[test]
+    fn credit_is_what_was_observed() {
+        assert_eq!(credited(Measured::NotGone, 400), 400 / MIN_CREDIT_DIVISOR);
+        assert_eq!(credited(Measured::Unreadable, 400), 400);
+        assert_eq!(credited(Measured::Freed(0), 400), 0);
+        assert_eq!(credited(Measured::Freed(50), 400), 50);
+        assert_eq!(credited(Measured::Freed(900), 400), 900);
+    }
+
+    #[test]
+    fn unmeasurable_kills_are_credited_their_estimate() {
+        let mut killer = Killer::new();
+        killer.meminfo_reader = None;
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut victim = champion(&mut killer, child.id());
+        victim.estimate = 400;
+        let pidfd = PidFd::open(victim.pid).ok();
+        let before = killer.sample_free(Relief::Memory);
+        assert_eq!(before, None);
+
+        let mechanism = mechanism_of(pidfd.as_ref());
+        let credit = killer.sigkill(
+            &victim,
+            pidfd.as_ref(),
+            "sleep",
+            mechanism,
+            (Relief::Memory, before),
+        );
+        child.wait().unwrap();
+        assert_eq!(credit, Some(400));
     }
 }
@@ -68,12 +68,9 @@ fn log_json(event: &SentinelEvent) {
     if let Some(map) = log_entry.as_object_mut() {
         map.insert("timestamp".into(), Utc::now().to_rfc3339().into());
         map.insert("level".into(), event.severity().as_str().into());
-        match event {
-            SentinelEvent::Message { level: _, text } => {
-                map.insert("message".into(), text.as_str().into());
-                map.remove("text");
-            }
-            _ => {}
+        if let SentinelEvent::Message { level: _, text } = event {
+            map.insert("message".into(), text.as_str().into());
+            map.remove("text");
         }
     }
 
@@ -86,10 +83,13 @@ fn emit_notification(event: &SentinelEvent) {
     match event {
         SentinelEvent::LowMemoryWarn { .. }
         | SentinelEvent::LowSwapWarn { .. }
+        | SentinelEvent::LowCombinedWarn { .. }
+        | SentinelEvent::HighUsageWarn { .. }
+        | SentinelEvent::ExhaustionPredicted { .. }
         | SentinelEvent::PsiPressureWarn { .. } => {
             send_notification("Low Memory Warning", &event.to_string(), "dialog-warning");
         }
-        SentinelEvent::KillExecuted { .. } => {
+        SentinelEvent::KillExecuted { .. } | SentinelEvent::ProcessFrozen { .. } => {
             send_notification("System Load Shedding", &event.to_string(), "process-stop");
         }
         SentinelEvent::KillTriggered { .. } => {
@@ -99,6 +99,13 @@ fn emit_notification(event: &SentinelEvent) {
                 "process-stop",
             );
         }
+        SentinelEvent::ConfigReloadFailed { .. } => {
+            send_notification(
+                "Configuration Reload Failed",
+                &event.to_string(),
+                "dialog-error",
+            );
+        }
         SentinelEvent::Message { level, text, .. } => match level {
             LogLevel::Warn => {
                 send_notification("Ram Sentinel Warning", text, "dialog-warning");
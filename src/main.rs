@@ -1,14 +1,23 @@
+mod cgroup;
+mod check;
 mod config;
 mod config_error;
+mod control;
 mod events;
 mod killer;
 mod logging; // Added
+mod matcher;
+mod meminfo;
 mod monitor;
+mod pidfd;
 mod psi;
+mod reload;
+mod swap;
 mod system;
 mod utils;
 
-use clap::Parser;
+use byte_unit::Byte;
+use clap::{Parser, Subcommand, ValueEnum};
 
 use nix::sys::signal::{SigHandler, Signal, signal};
 use std::fs;
@@ -16,21 +25,28 @@ use std::io::Write;
 use std::path::PathBuf;
 use std::process::exit;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+use crate::cgroup::ScopePattern;
 use crate::config::{Config, RuntimeContext};
+use crate::control::{ControlServer, ControlState, Request};
 use crate::events::{LogLevel, LogMode, SentinelEvent};
-use crate::killer::Killer;
+use crate::killer::{Candidate, Killer};
 use crate::monitor::{Monitor, MonitorStatus};
+use crate::reload::ConfigWatcher;
 use crate::system::get_systemd_unit; // Added
 
 static RUNNING: AtomicBool = AtomicBool::new(true);
+static RELOAD_REQUESTED: AtomicBool = AtomicBool::new(false);
 
 extern "C" fn handle_shutdown_signal(_: i32) {
     RUNNING.store(false, Ordering::SeqCst);
 }
 
+extern "C" fn handle_reload_signal(_: i32) {
+    RELOAD_REQUESTED.store(true, Ordering::SeqCst);
+}
+
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Cli {
@@ -50,6 +66,25 @@ struct Cli {
     #[arg(long)]
     no_kill: bool,
 
+    /// Reload the configuration file automatically when it changes on disk.
+    /// (SIGHUP always triggers a reload.)
+    #[arg(long)]
+    watch_config: bool,
+
+    /// Control socket path. Defaults to $XDG_RUNTIME_DIR/ram-sentinel.sock
+    /// (/run/ram-sentinel.sock when running as root).
+    #[arg(long, value_name = "PATH")]
+    control_socket: Option<PathBuf>,
+
+    /// Don't open the control socket.
+    #[arg(long, conflicts_with = "control_socket")]
+    no_control_socket: bool,
+
+    /// Print every process ranked in the order the kill sequence would pick it
+    /// (matched killTargets entry, ignoreNames hit, score, RSS), then exit.
+    #[arg(long, value_name = "FORMAT", num_args(0..=1), default_missing_value = "table")]
+    list_candidates: Option<ListFormat>,
+
     /// Optional Path to print configuration to. Defaults to stdout.
     #[arg(long, value_name = "FILE", num_args(0..=1), default_missing_value = "-")]
     print_config: Option<PathBuf>,
@@ -57,6 +92,34 @@ struct Cli {
     /// Optional Path to print systemd user unit to. Defaults to stdout.
     #[arg(long, value_name = "FILE", num_args(0..=1), default_missing_value = "-")]
     print_systemd_user_unit: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ListFormat {
+    Table,
+    Json,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Send a command to a running ram-sentinel over its control socket
+    Ctl {
+        /// Control socket path. Defaults to $XDG_RUNTIME_DIR/ram-sentinel.sock
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+
+        #[command(subcommand)]
+        request: Request,
+    },
+    /// Validate a configuration file and report every problem found, then exit.
+    /// Uses the same lookup as startup when FILE is omitted
+    CheckConfig {
+        #[arg(value_name = "FILE")]
+        file: Option<PathBuf>,
+    },
 }
 
 fn handle_output(path_arg: Option<PathBuf>, content: &str) {
@@ -93,6 +156,16 @@ fn main() {
     logging::set_logging_mode(args.log_format);
     logging::set_logging_level(args.log_level);
 
+    match &args.command {
+        Some(Command::Ctl { socket, request }) => {
+            exit(control::run_client(socket.clone(), request));
+        }
+        Some(Command::CheckConfig { file }) => {
+            exit(check::run(file.clone().or_else(|| args.config.clone())));
+        }
+        None => {}
+    }
+
     // Register signal handlers
     unsafe {
         let handler = SigHandler::Handler(handle_shutdown_signal);
@@ -108,6 +181,13 @@ fn main() {
                 text: format!("Failed to register SIGINT handler: {}", e),
             });
         }
+        let reload_handler = SigHandler::Handler(handle_reload_signal);
+        if let Err(e) = signal(Signal::SIGHUP, reload_handler) {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Error,
+                text: format!("Failed to register SIGHUP handler: {}", e),
+            });
+        }
     }
 
     // --- Handle Utility Flags ---
@@ -124,7 +204,7 @@ fn main() {
         return;
     }
 
-    let ctx = match Config::load(args.config) {
+    let ctx = match Config::load(args.config.clone()) {
         Ok(c) => c,
         Err(e) => {
             logging::emit(&SentinelEvent::Message {
@@ -135,33 +215,153 @@ fn main() {
         }
     };
 
-    run_loop(ctx, args.no_kill);
+    if let Some(format) = args.list_candidates {
+        let candidates = Killer::new().list_candidates(&ctx);
+        print_candidates(&candidates, format);
+        return;
+    }
+
+    run_loop(ctx, args);
 }
 
-fn run_loop(ctx: RuntimeContext, no_kill: bool) {
+fn print_candidates(candidates: &[Candidate], format: ListFormat) {
+    match format {
+        ListFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(candidates).expect("candidates serialize")
+        ),
+        ListFormat::Table => {
+            println!(
+                "{:>4}  {:>7}  {:<16}  {:<32}  {:>8}  {:>10}",
+                "RANK", "PID", "NAME", "MATCH", "SCORE", "RSS"
+            );
+            for (rank, c) in candidates.iter().enumerate() {
+                let rank = if c.ignored_by.is_some() {
+                    "-".to_string()
+                } else {
+                    (rank + 1).to_string()
+                };
+                let matched = match (&c.ignored_by, c.match_index, &c.target_pattern) {
+                    (Some(pattern), _, _) => format!("ignored: {}", pattern),
+                    (None, Some(i), Some(pattern)) => format!("#{} {}", i, pattern),
+                    _ => "-".to_string(),
+                };
+                let rss = Byte::from_u64(c.rss)
+                    .get_appropriate_unit(byte_unit::UnitType::Decimal)
+                    .to_string();
+                println!(
+                    "{:>4}  {:>7}  {:<16}  {:<32}  {:>8}  {:>10}",
+                    rank, c.pid, c.process_name, matched, c.score, rss
+                );
+            }
+        }
+    }
+}
+
+/// Paces resuming suspended targets: one at a time, each after a full healthy dwell.
+#[derive(Default)]
+struct ThawDwell {
+    healthy_since: Option<Instant>,
+}
+
+impl ThawDwell {
+    /// Records one check. Returns true when a suspended target is due to be resumed.
+    fn tick(&mut self, healthy: bool, has_suspended: bool, dwell: Duration, now: Instant) -> bool {
+        if !healthy {
+            self.healthy_since = None;
+            return false;
+        }
+        let since = *self.healthy_since.get_or_insert(now);
+        if has_suspended && now.duration_since(since) >= dwell {
+            // The next one waits a full dwell again
+            self.healthy_since = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+fn run_loop(mut ctx: RuntimeContext, args: Cli) {
     let mut monitor = Monitor::new();
     let mut killer = Killer::new();
+    let mut watcher = args.watch_config.then(|| ConfigWatcher::new(&ctx));
+    let mut control_state = ControlState::new(args.no_kill);
+    let control = open_control_socket(&args);
+    let mut thaw = ThawDwell::default();
+
+    if let Some(w) = &watcher
+        && w.path().is_none()
+    {
+        logging::emit(&SentinelEvent::Message {
+            level: LogLevel::Warn,
+            text:
+                "--watch-config active but no configuration file is loaded. Use SIGHUP to reload."
+                    .to_string(),
+        });
+    }
 
     logging::emit(&SentinelEvent::Startup {
         interval_ms: ctx.check_interval_ms,
     });
 
     while RUNNING.load(Ordering::SeqCst) {
-        match monitor.check(&ctx) {
-            MonitorStatus::Normal => {}
-            MonitorStatus::Warn => {}
-            MonitorStatus::Kill(event) => {
+        // Hot-reload: swap in the new context only if it fully validates
+        let reload_trigger = if RELOAD_REQUESTED.swap(false, Ordering::SeqCst) {
+            Some("SIGHUP")
+        } else if watcher.as_mut().is_some_and(|w| w.has_changed()) {
+            Some("FileChanged")
+        } else {
+            None
+        };
+        if let Some(trigger) = reload_trigger {
+            if let Some(new_ctx) = reload::reload(&ctx, args.config.clone(), trigger) {
+                ctx = new_ctx;
+            }
+            if let Some(w) = watcher.as_mut() {
+                w.rebind(&ctx);
+            }
+        }
+
+        let status = monitor.check(&ctx);
+        if thaw.tick(
+            matches!(status, MonitorStatus::Normal),
+            killer.has_suspended(),
+            Duration::from_millis(ctx.thaw_dwell_ms),
+            Instant::now(),
+        ) {
+            killer.thaw_latest();
+        }
+        match status {
+            MonitorStatus::Normal | MonitorStatus::Warn => {}
+            MonitorStatus::Kill(event, relief) => {
                 logging::emit(&event);
 
-                if no_kill {
+                if control_state.dry_run {
+                    logging::emit(&SentinelEvent::Message {
+                        level: LogLevel::Info,
+                        text: "Dry run active. Skipping kill sequence.".to_string(),
+                    });
+                } else if let Some(left) = control_state.paused_for() {
                     logging::emit(&SentinelEvent::Message {
                         level: LogLevel::Info,
-                        text: "--no-kill active. Skipping kill sequence.".to_string(),
+                        text: format!("Paused ({}s left). Skipping kill sequence.", left.as_secs()),
                     });
                 } else {
-                    if let SentinelEvent::KillTriggered { amount_needed, .. } = &event {
+                    if let SentinelEvent::KillTriggered {
+                        amount_needed,
+                        cgroup,
+                        ..
+                    } = &*event
+                    {
                         if let Some(needed) = *amount_needed {
-                            killer.kill_sequence(&ctx, Some(needed));
+                            // A cgroup-scoped trigger is only relieved by killing inside that cgroup
+                            let trigger_scope = cgroup.as_deref().map(ScopePattern::for_cgroup);
+                            killer.kill_sequence(
+                                &ctx,
+                                Some(needed),
+                                trigger_scope.as_ref(),
+                                relief,
+                            );
                         } else {
                             logging::emit(&SentinelEvent::KillSequenceAborted {
                                 reason: "Kill triggered but amount_needed is None/Zero".to_string(),
@@ -177,11 +377,106 @@ fn run_loop(ctx: RuntimeContext, no_kill: bool) {
                 }
             }
         }
-        sleep(Duration::from_millis(ctx.check_interval_ms));
+        // Sleep until the next check. Control requests are answered in between
+        // without cutting the interval short; PSI triggers and signals do.
+        let next_check = Instant::now() + Duration::from_millis(ctx.check_interval_ms);
+        loop {
+            let remaining = next_check.saturating_duration_since(Instant::now());
+            let triggered = monitor.wait(remaining, control.as_ref().map(|c| c.as_fd()));
+            let Some(server) = &control else { break };
+            server.serve(&mut control_state, &ctx, &monitor, &mut killer);
+            if triggered
+                || Instant::now() >= next_check
+                || !RUNNING.load(Ordering::SeqCst)
+                || RELOAD_REQUESTED.load(Ordering::SeqCst)
+            {
+                break;
+            }
+        }
     }
 
+    // Never leave targets paused behind us
+    killer.thaw_all();
+
     logging::emit(&SentinelEvent::Message {
         level: LogLevel::Info,
         text: "Exiting ram-sentinel.".to_string(),
     });
 }
+
+fn open_control_socket(args: &Cli) -> Option<ControlServer> {
+    if args.no_control_socket {
+        return None;
+    }
+    let Some(path) = args
+        .control_socket
+        .clone()
+        .or_else(control::default_socket_path)
+    else {
+        logging::emit(&SentinelEvent::Message {
+            level: LogLevel::Warn,
+            text: "XDG_RUNTIME_DIR is not set; control socket disabled. Use --control-socket."
+                .to_string(),
+        });
+        return None;
+    };
+    match ControlServer::bind(&path) {
+        Ok(server) => {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Debug,
+                text: format!("Control socket listening on {:?}", server.path()),
+            });
+            Some(server)
+        }
+        Err(e) => {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Warn,
+                text: format!("Control socket {:?} unavailable: {}", path, e),
+            });
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thaws_one_target_per_healthy_dwell() {
+        let dwell = Duration::from_secs(30);
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let mut thaw = ThawDwell::default();
+
+        assert!(!thaw.tick(true, true, dwell, at(0)));
+        assert!(!thaw.tick(true, true, dwell, at(29)));
+        assert!(thaw.tick(true, true, dwell, at(30)));
+        // The dwell restarts after each thaw
+        assert!(!thaw.tick(true, true, dwell, at(45)));
+        assert!(thaw.tick(true, true, dwell, at(60)));
+    }
+
+    #[test]
+    fn unhealthy_check_restarts_the_dwell() {
+        let dwell = Duration::from_secs(30);
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+        let mut thaw = ThawDwell::default();
+
+        assert!(!thaw.tick(true, true, dwell, at(0)));
+        assert!(!thaw.tick(false, true, dwell, at(20)));
+        assert!(!thaw.tick(true, true, dwell, at(40)));
+        assert!(thaw.tick(true, true, dwell, at(70)));
+    }
+
+    #[test]
+    fn nothing_suspended_nothing_to_thaw() {
+        let dwell = Duration::from_secs(30);
+        let t0 = Instant::now();
+        let mut thaw = ThawDwell::default();
+
+        assert!(!thaw.tick(true, false, dwell, t0));
+        assert!(!thaw.tick(true, false, dwell, t0 + Duration::from_secs(60)));
+    }
+}
@@ -0,0 +1,565 @@
+use crate::cgroup::{ScopePattern, unified_cgroup_path};
+use crate::config::Pattern;
+use crate::config_error::ConfigError;
+use crate::meminfo::{parse_stat, read_from_start};
+use nix::unistd::User;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::os::unix::fs::MetadataExt;
+
+// comm is at most 15 bytes (TASK_COMM_LEN - 1); exe and cgroup paths at most PATH_MAX
+pub const MAX_COMM_LEN: usize = 15;
+const COMM_BUFFER_SIZE: usize = 64;
+const PATH_BUFFER_SIZE: usize = 4096;
+const STAT_BUFFER_SIZE: usize = 1024;
+
+// How far up the parent chain `ancestor` looks unless `ancestorDepth` says otherwise
+const DEFAULT_ANCESTOR_DEPTH: u32 = 32;
+
+/// Object form of a `killTargets`/`ignoreNames` entry. Every field given must match.
+/// A bare string entry is the same as `{ cmdline: "..." }`.
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct MatcherConfig {
+    /// Space-joined command line (`pattern` in older configs).
+    #[serde(alias = "pattern")]
+    pub cmdline: Option<String>,
+    /// Process name (/proc/<pid>/comm, at most 15 characters).
+    pub comm: Option<String>,
+    /// Executable path (/proc/<pid>/exe); globs with '*' and '?' match the whole path.
+    pub exe: Option<String>,
+    /// Owning user, by name or numeric uid.
+    pub user: Option<String>,
+    /// cgroup v2 path or glob, as in `killScope`.
+    pub cgroup: Option<String>,
+    /// comm of the parent process.
+    pub parent: Option<String>,
+    /// comm of any ancestor (parent, grandparent, ...), never of the process itself.
+    /// Like `comm` and `parent`, it only ever sees the first 15 characters of a name.
+    pub ancestor: Option<String>,
+    /// How many levels `ancestor` walks up (default 32).
+    pub ancestor_depth: Option<u32>,
+}
+
+impl MatcherConfig {
+    pub fn from_pattern(pattern: &str) -> Self {
+        Self {
+            cmdline: Some(pattern.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// The cmdline pattern if that's all the entry checks (it then reads like a string entry).
+    pub fn cmdline_only(&self) -> Option<&str> {
+        match self {
+            Self {
+                cmdline: Some(cmdline),
+                comm: None,
+                exe: None,
+                user: None,
+                cgroup: None,
+                parent: None,
+                ancestor: None,
+                ancestor_depth: None,
+            } => Some(cmdline),
+            _ => None,
+        }
+    }
+
+    fn fields(&self) -> [(&'static str, Option<&String>); 7] {
+        [
+            ("cmdline", self.cmdline.as_ref()),
+            ("comm", self.comm.as_ref()),
+            ("exe", self.exe.as_ref()),
+            ("user", self.user.as_ref()),
+            ("cgroup", self.cgroup.as_ref()),
+            ("parent", self.parent.as_ref()),
+            ("ancestor", self.ancestor.as_ref()),
+        ]
+    }
+
+    /// The comm-based fields (comm, parent, ancestor) whose literal text is longer than a comm
+    /// can be, so they never match. Regexes aren't judged.
+    pub fn overlong_comm_fields(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [
+            ("comm", &self.comm),
+            ("parent", &self.parent),
+            ("ancestor", &self.ancestor),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let value = value.as_deref()?;
+            let text = match Pattern::parse(value).ok()? {
+                Pattern::Literal(text) | Pattern::StartsWith(text) => text,
+                _ => return None,
+            };
+            (text.len() > MAX_COMM_LEN).then_some((name, value))
+        })
+    }
+
+    /// The path-holding fields (cmdline, exe) whose value reads as a /regex/ but looks like a
+    /// directory path, e.g. "/usr/lib/chromium/": '/' inside and no regex syntax besides '.'.
+    pub fn path_like_regex_fields(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        [("cmdline", &self.cmdline), ("exe", &self.exe)]
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let value = value.as_deref()?;
+                let inner = value.strip_prefix('/')?.strip_suffix('/')?;
+                let path_like = inner.contains('/')
+                    && !inner.contains([
+                        '\\', '^', '$', '|', '?', '*', '+', '(', ')', '[', ']', '{', '}',
+                    ]);
+                path_like.then_some((name, value))
+            })
+    }
+
+    /// One line for logs and listings, e.g. "exe=/usr/lib/chromium/* user=alice".
+    pub fn describe(&self) -> String {
+        if let Some(cmdline) = self.cmdline_only() {
+            return cmdline.to_string();
+        }
+        let mut terms: Vec<String> = self
+            .fields()
+            .iter()
+            .filter_map(|(name, value)| value.map(|v| format!("{}={}", name, v)))
+            .collect();
+        if let Some(depth) = self.ancestor_depth {
+            terms.push(format!("ancestorDepth={}", depth));
+        }
+        terms.join(" ")
+    }
+}
+
+/// A compiled `MatcherConfig`.
+#[derive(Debug)]
+pub struct Matcher {
+    cmdline: Option<Pattern>,
+    comm: Option<Pattern>,
+    exe: Option<Pattern>,
+    uid: Option<u32>,
+    cgroup: Option<ScopePattern>,
+    parent: Option<Pattern>,
+    ancestor: Option<Pattern>,
+    ancestor_depth: u32,
+}
+
+impl Matcher {
+    /// `field` and `index` locate the entry in error messages ("kill_targets", 3).
+    pub fn compile(config: &MatcherConfig, field: &str, index: usize) -> Result<Self, ConfigError> {
+        let invalid = |reason: String| {
+            ConfigError::Matcher(field.to_string(), index, config.describe(), reason)
+        };
+        if config.fields().iter().all(|(_, value)| value.is_none()) {
+            return Err(invalid(
+                "no fields given, it would match every process".to_string(),
+            ));
+        }
+
+        // Regex errors name the offending field value, as for string entries
+        let pattern = |raw: Option<&String>, glob: bool| {
+            raw.map(|s| {
+                let parsed = if glob {
+                    Pattern::parse_glob(s)
+                } else {
+                    Pattern::parse(s)
+                };
+                parsed.map_err(|e| {
+                    ConfigError::RegexError(field.to_string(), index, s.clone(), e.to_string())
+                })
+            })
+            .transpose()
+        };
+        let ancestor_depth = match (&config.ancestor, config.ancestor_depth) {
+            (None, Some(_)) => return Err(invalid("ancestorDepth without ancestor".to_string())),
+            (_, Some(0)) => return Err(invalid("ancestorDepth must be at least 1".to_string())),
+            (_, depth) => depth.unwrap_or(DEFAULT_ANCESTOR_DEPTH),
+        };
+        let uid = match &config.user {
+            None => None,
+            Some(user) => Some(
+                resolve_user(user).ok_or_else(|| invalid(format!("unknown user '{}'", user)))?,
+            ),
+        };
+        let cgroup = match &config.cgroup {
+            None => None,
+            Some(raw) => {
+                Some(ScopePattern::parse(raw).ok_or_else(|| invalid("empty cgroup".to_string()))?)
+            }
+        };
+
+        Ok(Self {
+            cmdline: pattern(config.cmdline.as_ref(), false)?,
+            comm: pattern(config.comm.as_ref(), false)?,
+            exe: pattern(config.exe.as_ref(), true)?,
+            uid,
+            cgroup,
+            parent: pattern(config.parent.as_ref(), false)?,
+            ancestor: pattern(config.ancestor.as_ref(), false)?,
+            ancestor_depth,
+        })
+    }
+
+    /// `cmdline` is the space-joined command line; other facts are read from `facts` on demand,
+    /// cheapest checks first.
+    pub fn matches(&self, cmdline: &str, facts: &mut ProcFacts) -> bool {
+        self.cmdline.as_ref().is_none_or(|p| p.matches(cmdline))
+            && self.uid.is_none_or(|uid| facts.uid() == Some(uid))
+            && self
+                .comm
+                .as_ref()
+                .is_none_or(|p| facts.comm().is_some_and(|c| p.matches(c)))
+            && self
+                .exe
+                .as_ref()
+                .is_none_or(|p| facts.exe().is_some_and(|e| p.matches(e)))
+            && self
+                .cgroup
+                .as_ref()
+                .is_none_or(|s| facts.cgroup().is_some_and(|c| s.matches(c)))
+            && self
+                .parent
+                .as_ref()
+                .is_none_or(|p| facts.parent_comm().is_some_and(|c| p.matches(c)))
+            && self
+                .ancestor
+                .as_ref()
+                .is_none_or(|p| facts.has_ancestor(p, self.ancestor_depth))
+    }
+}
+
+fn resolve_user(user: &str) -> Option<u32> {
+    if let Ok(uid) = user.parse::<u32>() {
+        return Some(uid);
+    }
+    User::from_name(user).ok().flatten().map(|u| u.uid.as_raw())
+}
+
+/// What the matchers know about one process besides its cmdline. Each fact is read on first
+/// use into a buffer allocated once, so matching in the kill sequence doesn't allocate.
+pub struct ProcFacts {
+    pid: u32,
+    path: String,
+    stat: Vec<u8>,
+    comm: Fact,
+    exe: Fact,
+    cgroup: Fact,
+    parent_comm: Fact,
+    uid: Option<Option<u32>>,
+}
+
+// A fact's bytes; `len` is None until read, Some(None) if unavailable
+struct Fact {
+    buffer: Vec<u8>,
+    len: Option<Option<usize>>,
+}
+
+impl Fact {
+    fn new(size: usize) -> Self {
+        Self {
+            buffer: vec![0; size],
+            len: None,
+        }
+    }
+
+    fn get(&self) -> Option<&str> {
+        let len = self.len.flatten()?;
+        std::str::from_utf8(&self.buffer[..len]).ok()
+    }
+}
+
+impl ProcFacts {
+    pub fn new() -> Self {
+        Self {
+            pid: 0,
+            path: String::with_capacity(64),
+            stat: vec![0; STAT_BUFFER_SIZE],
+            comm: Fact::new(COMM_BUFFER_SIZE),
+            exe: Fact::new(PATH_BUFFER_SIZE),
+            cgroup: Fact::new(PATH_BUFFER_SIZE),
+            parent_comm: Fact::new(COMM_BUFFER_SIZE),
+            uid: None,
+        }
+    }
+
+    /// Forgets everything read about the previous process.
+    pub fn reset(&mut self, pid: u32) {
+        self.pid = pid;
+        for fact in [
+            &mut self.comm,
+            &mut self.exe,
+            &mut self.cgroup,
+            &mut self.parent_comm,
+        ] {
+            fact.len = None;
+        }
+        self.uid = None;
+    }
+
+    /// Owner of /proc/<pid> (the effective uid).
+    pub fn uid(&mut self) -> Option<u32> {
+        if self.uid.is_none() {
+            self.set_path(self.pid, "");
+            self.uid = Some(fs::metadata(&self.path).ok().map(|m| m.uid()));
+        }
+        self.uid.flatten()
+    }
+
+    pub fn comm(&mut self) -> Option<&str> {
+        if self.comm.len.is_none() {
+            self.comm.len = Some(read_comm(self.pid, &mut self.path, &mut self.comm.buffer));
+        }
+        self.comm.get()
+    }
+
+    /// Target of /proc/<pid>/exe. Only readable for processes we may ptrace.
+    pub fn exe(&mut self) -> Option<&str> {
+        if self.exe.len.is_none() {
+            // NUL-terminated for readlink(2)
+            self.set_path(self.pid, "exe\0");
+            let buffer = &mut self.exe.buffer;
+            let n = unsafe {
+                libc::readlink(
+                    self.path.as_ptr() as *const libc::c_char,
+                    buffer.as_mut_ptr() as *mut libc::c_char,
+                    buffer.len(),
+                )
+            };
+            self.exe.len = Some((n >= 0).then_some(n as usize));
+        }
+        self.exe.get()
+    }
+
+    /// cgroup v2 path, from the "0::" line of /proc/<pid>/cgroup.
+    pub fn cgroup(&mut self) -> Option<&str> {
+        if self.cgroup.len.is_none() {
+            self.set_path(self.pid, "cgroup");
+            let buffer = &mut self.cgroup.buffer;
+            let len = File::open(&self.path)
+                .and_then(|f| read_from_start(&f, buffer))
+                .ok()
+                .and_then(|n| {
+                    // Move the path to the front so it can be handed out as is
+                    let path = unified_cgroup_path(&buffer[..n])?;
+                    let start = path.as_ptr() as usize - buffer.as_ptr() as usize;
+                    let len = path.len();
+                    buffer.copy_within(start..start + len, 0);
+                    Some(len)
+                });
+            self.cgroup.len = Some(len);
+        }
+        self.cgroup.get()
+    }
+
+    /// comm of the parent (ppid from /proc/<pid>/stat).
+    pub fn parent_comm(&mut self) -> Option<&str> {
+        if self.parent_comm.len.is_none() {
+            let len = self
+                .ppid()
+                .and_then(|ppid| read_comm(ppid, &mut self.path, &mut self.parent_comm.buffer));
+            self.parent_comm.len = Some(len);
+        }
+        self.parent_comm.get()
+    }
+
+    /// Whether the parent, grandparent, ... (at most `max_depth` levels up) has a comm matching
+    /// `pattern`. Not cached: each level re-reads a /proc/<pid>/stat into the `stat` buffer and
+    /// rewrites `path` in place, so the walk doesn't allocate. It can't use the killer's
+    /// `read_buffer`: that holds the cmdline being matched for as long as `matches` runs.
+    pub fn has_ancestor(&mut self, pattern: &Pattern, max_depth: u32) -> bool {
+        let Some((mut ppid, _)) = self.read_stat(self.pid) else {
+            return false;
+        };
+        for _ in 0..max_depth {
+            // 0: above init (and kthreadd)
+            if ppid == 0 {
+                return false;
+            }
+            let Some((next, comm)) = self.read_stat(ppid) else {
+                return false; // Exited meanwhile; the chain is broken
+            };
+            if pattern.matches(comm) {
+                return true;
+            }
+            ppid = next;
+        }
+        false
+    }
+
+    fn ppid(&mut self) -> Option<u32> {
+        self.read_stat(self.pid).map(|(ppid, _)| ppid)
+    }
+
+    // (ppid, comm) of /proc/<pid>/stat
+    fn read_stat(&mut self, pid: u32) -> Option<(u32, &str)> {
+        self.set_path(pid, "stat");
+        let file = File::open(&self.path).ok()?;
+        let n = read_from_start(&file, &mut self.stat).ok()?;
+        let stat = parse_stat(std::str::from_utf8(&self.stat[..n]).ok()?)?;
+        Some((stat.ppid, stat.comm))
+    }
+
+    fn set_path(&mut self, pid: u32, file: &str) {
+        self.path.clear();
+        write!(self.path, "/proc/{}/{}", pid, file).unwrap();
+    }
+}
+
+fn read_comm(pid: u32, path: &mut String, buffer: &mut [u8]) -> Option<usize> {
+    path.clear();
+    write!(path, "/proc/{}/comm", pid).unwrap();
+    let file = File::open(&*path).ok()?;
+    let n = read_from_start(&file, buffer).ok()?;
+    Some(buffer[..n].trim_ascii_end().len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The reason compile gives for rejecting `config`, or "ok"
+    fn rejection(config: MatcherConfig) -> String {
+        match Matcher::compile(&config, "kill_targets", 0) {
+            Ok(_) => "ok".to_string(),
+            Err(ConfigError::Matcher(_, _, _, reason)) => reason,
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn cmdline_only_entries_read_like_strings() {
+        let plain = MatcherConfig::from_pattern("^node");
+        assert_eq!(plain.cmdline_only(), Some("^node"));
+        assert_eq!(plain.describe(), "^node");
+
+        let object = MatcherConfig {
+            cmdline: Some("npm".to_string()),
+            user: Some("alice".to_string()),
+            ancestor: Some("bash".to_string()),
+            ancestor_depth: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(object.cmdline_only(), None);
+        assert_eq!(
+            object.describe(),
+            "cmdline=npm user=alice ancestor=bash ancestorDepth=2"
+        );
+    }
+
+    #[test]
+    fn compile_rejects_unusable_entries() {
+        assert!(rejection(MatcherConfig::default()).starts_with("no fields given"));
+        let empty_cgroup = MatcherConfig {
+            cgroup: Some(" ".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rejection(empty_cgroup), "empty cgroup");
+        let unknown_user = MatcherConfig {
+            user: Some("no-such-user-here".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(rejection(unknown_user), "unknown user 'no-such-user-here'");
+        let bad_regex = MatcherConfig {
+            exe: Some("/(/".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Matcher::compile(&bad_regex, "kill_targets", 3),
+            Err(ConfigError::RegexError(_, 3, pattern, _)) if pattern == "/(/"
+        ));
+    }
+
+    #[test]
+    fn every_field_must_match() {
+        let uid = nix::unistd::getuid().as_raw().to_string();
+        let own = MatcherConfig {
+            user: Some(uid.clone()),
+            ..Default::default()
+        };
+        let other = MatcherConfig {
+            user: Some(uid),
+            comm: Some("no-such-comm".to_string()),
+            ..Default::default()
+        };
+        let mut facts = ProcFacts::new();
+        facts.reset(std::process::id());
+        let own = Matcher::compile(&own, "kill_targets", 0).unwrap();
+        let other = Matcher::compile(&other, "kill_targets", 1).unwrap();
+        assert!(own.matches("", &mut facts));
+        assert!(!other.matches("", &mut facts));
+    }
+
+    #[test]
+    fn flags_overlong_literal_comm_fields() {
+        let config = MatcherConfig {
+            comm: Some("chromium-browser".to_string()),
+            parent: Some("^gnome-shell-calendar".to_string()),
+            ancestor: Some("/systemd-journald-audit/".to_string()),
+            ..Default::default()
+        };
+        let fields: Vec<_> = config.overlong_comm_fields().collect();
+        assert_eq!(
+            fields,
+            [
+                ("comm", "chromium-browser"),
+                ("parent", "^gnome-shell-calendar")
+            ]
+        );
+        let fits = MatcherConfig {
+            comm: Some("chromium-browse".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(fits.overlong_comm_fields().count(), 0);
+    }
+
+    #[test]
+    fn flags_paths_that_parse_as_regexes() {
+        let config = MatcherConfig {
+            exe: Some("/usr/lib/chromium/".to_string()),
+            cmdline: Some("/opt/app.d/".to_string()),
+            ..Default::default()
+        };
+        let fields: Vec<_> = config.path_like_regex_fields().collect();
+        assert_eq!(
+            fields,
+            [("cmdline", "/opt/app.d/"), ("exe", "/usr/lib/chromium/")]
+        );
+        let intended = MatcherConfig {
+            exe: Some("/usr/lib/.*/chrome$/".to_string()),
+            cmdline: Some("/--type=renderer/".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(intended.path_like_regex_fields().count(), 0);
+        let glob = MatcherConfig {
+            exe: Some("/usr/lib/chromium/*".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(glob.path_like_regex_fields().count(), 0);
+    }
+
+    #[test]
+    fn ancestor_depth_needs_ancestor() {
+        let without = MatcherConfig {
+            comm: Some("node".to_string()),
+            ancestor_depth: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(rejection(without), "ancestorDepth without ancestor");
+        let zero = MatcherConfig {
+            ancestor: Some("bash".to_string()),
+            ancestor_depth: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(rejection(zero), "ancestorDepth must be at least 1");
+    }
+
+    #[test]
+    fn ancestor_walk_sees_the_parent() {
+        let mut facts = ProcFacts::new();
+        facts.reset(std::process::id());
+        let parent = Pattern::Literal(facts.parent_comm().unwrap().to_string());
+        assert!(facts.has_ancestor(&parent, 1));
+        let missing = Pattern::Literal("no-such-comm".to_string());
+        assert!(!facts.has_ancestor(&missing, DEFAULT_ANCESTOR_DEPTH));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::config::UsageConfigParsed;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+
+pub const MEMINFO_PATH: &str = "/proc/meminfo";
+
+// /proc/meminfo is ~1.5KiB on current kernels
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// The /proc/meminfo fields we use, in bytes. Fields this kernel doesn't report stay None.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct MemInfo {
+    pub mem_total: u64,
+    pub mem_available: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    pub swap_cached: Option<u64>,
+    pub dirty: Option<u64>,
+    pub writeback: Option<u64>,
+    pub shmem: Option<u64>,
+    pub mlocked: Option<u64>,
+    pub zswap: Option<u64>,    // Compressed size of the zswap pool (5.19+)
+    pub zswapped: Option<u64>, // Uncompressed size of what it holds
+    pub unevictable: Option<u64>,
+}
+
+/// Reads /proc/meminfo without allocating: the file stays open and is re-read from offset 0
+/// into a buffer allocated once.
+pub struct MemInfoReader {
+    file: File,
+    buffer: Vec<u8>,
+}
+
+impl MemInfoReader {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            file: File::open(MEMINFO_PATH)?,
+            buffer: vec![0; BUFFER_SIZE],
+        })
+    }
+
+    pub fn read(&mut self) -> io::Result<MemInfo> {
+        let len = read_from_start(&self.file, &mut self.buffer)?;
+        parse_meminfo(&self.buffer[..len])
+    }
+}
+
+/// Re-reads a procfs/sysfs file that is kept open, filling `buffer` (never growing it).
+pub fn read_from_start(file: &File, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut len = 0;
+    while len < buffer.len() {
+        match file.read_at(&mut buffer[len..], len as u64)? {
+            0 => break,
+            n => len += n,
+        }
+    }
+    Ok(len)
+}
+
+fn parse_meminfo(content: &[u8]) -> io::Result<MemInfo> {
+    let mut info = MemInfo::default();
+    let (mut has_total, mut has_available) = (false, false);
+
+    for line in content.split(|&b| b == b'\n') {
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            continue;
+        };
+        let Some(value) = parse_kb(&line[colon + 1..]) else {
+            continue;
+        };
+        match &line[..colon] {
+            b"MemTotal" => {
+                info.mem_total = value;
+                has_total = true;
+            }
+            b"MemAvailable" => {
+                info.mem_available = value;
+                has_available = true;
+            }
+            b"SwapTotal" => info.swap_total = value,
+            b"SwapFree" => info.swap_free = value,
+            b"SwapCached" => info.swap_cached = Some(value),
+            b"Dirty" => info.dirty = Some(value),
+            b"Writeback" => info.writeback = Some(value),
+            b"Shmem" => info.shmem = Some(value),
+            b"Mlocked" => info.mlocked = Some(value),
+            b"Zswap" => info.zswap = Some(value),
+            b"Zswapped" => info.zswapped = Some(value),
+            b"Unevictable" => info.unevictable = Some(value),
+            _ => {}
+        }
+    }
+
+    if !has_total || !has_available {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "MemTotal/MemAvailable missing from /proc/meminfo",
+        ));
+    }
+    Ok(info)
+}
+
+/// Value of a "Key:   12345 kB" line (as in /proc/<pid>/status, smaps_rollup), in bytes.
+pub fn kb_field(content: &[u8], key: &[u8]) -> Option<u64> {
+    content.split(|&b| b == b'\n').find_map(|line| {
+        let value = line.strip_prefix(key)?.strip_prefix(b":")?;
+        parse_kb(value)
+    })
+}
+
+// "   12345 kB" -> bytes
+fn parse_kb(value: &[u8]) -> Option<u64> {
+    // Every field we read is in kB; the few unitless ones (HugePages_*) aren't matched
+    parse_number(value)?.checked_mul(1024)
+}
+
+/// Leading decimal number of `value`, after optional whitespace. Allocation-free.
+pub fn parse_number(value: &[u8]) -> Option<u64> {
+    let digits = value.trim_ascii_start();
+    let end = digits
+        .iter()
+        .position(|b| !b.is_ascii_digit())
+        .unwrap_or(digits.len());
+    if end == 0 {
+        return None;
+    }
+    let mut n: u64 = 0;
+    for &b in &digits[..end] {
+        n = n.checked_mul(10)?.checked_add((b - b'0') as u64)?;
+    }
+    Some(n)
+}
+
+/// The fields of a /proc/[pid]/stat line the daemon uses.
+#[derive(Debug, PartialEq)]
+pub struct ProcStat<'a> {
+    pub comm: &'a str,
+    pub state: char,
+    pub ppid: u32,
+    pub cpu_time: u64,   // utime + stime, in clock ticks
+    pub start_time: u64, // In clock ticks after boot
+}
+
+/// Parses "pid (comm) state ppid ...". Allocation-free; borrows `comm` from `stat`.
+pub fn parse_stat(stat: &str) -> Option<ProcStat<'_>> {
+    // comm may itself contain ") "; the last one ends it
+    let (before, after_comm) = stat.rsplit_once(") ")?;
+    let (_, comm) = before.split_once('(')?;
+    // after_comm starts at field 3 (state); utime is field 14, stime 15, starttime 22
+    let mut fields = after_comm.split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let mut fields = fields.skip(9);
+    let utime = fields.next()?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+    let start_time = fields.nth(6)?.parse().ok()?;
+    Some(ProcStat {
+        comm,
+        state,
+        ppid,
+        cpu_time: utime + stime,
+        start_time,
+    })
+}
+
+/// /proc/meminfo fields that can carry their own limits (`meminfo:` in the config).
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum MemInfoField {
+    SwapCached,
+    Dirty,
+    Writeback,
+    Shmem,
+    Mlocked,
+    Zswap,
+    Zswapped,
+    Unevictable,
+}
+
+impl MemInfoField {
+    /// Key in the `meminfo` config section.
+    pub fn config_key(&self) -> &'static str {
+        match self {
+            MemInfoField::SwapCached => "swapCached",
+            MemInfoField::Dirty => "dirty",
+            MemInfoField::Writeback => "writeback",
+            MemInfoField::Shmem => "shmem",
+            MemInfoField::Mlocked => "mlocked",
+            MemInfoField::Zswap => "zswap",
+            MemInfoField::Zswapped => "zswapped",
+            MemInfoField::Unevictable => "unevictable",
+        }
+    }
+
+    /// Name as in /proc/meminfo.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MemInfoField::SwapCached => "SwapCached",
+            MemInfoField::Dirty => "Dirty",
+            MemInfoField::Writeback => "Writeback",
+            MemInfoField::Shmem => "Shmem",
+            MemInfoField::Mlocked => "Mlocked",
+            MemInfoField::Zswap => "Zswap",
+            MemInfoField::Zswapped => "Zswapped",
+            MemInfoField::Unevictable => "Unevictable",
+        }
+    }
+
+    /// Name of its kill trigger, as reported in KillTriggered.
+    pub fn trigger(&self) -> &'static str {
+        match self {
+            MemInfoField::SwapCached => "HighSwapCached",
+            MemInfoField::Dirty => "HighDirty",
+            MemInfoField::Writeback => "HighWriteback",
+            MemInfoField::Shmem => "HighShmem",
+            MemInfoField::Mlocked => "HighMlocked",
+            MemInfoField::Zswap => "HighZswap",
+            MemInfoField::Zswapped => "HighZswapped",
+            MemInfoField::Unevictable => "HighUnevictable",
+        }
+    }
+
+    pub fn get(&self, info: &MemInfo) -> Option<u64> {
+        match self {
+            MemInfoField::SwapCached => info.swap_cached,
+            MemInfoField::Dirty => info.dirty,
+            MemInfoField::Writeback => info.writeback,
+            MemInfoField::Shmem => info.shmem,
+            MemInfoField::Mlocked => info.mlocked,
+            MemInfoField::Zswap => info.zswap,
+            MemInfoField::Zswapped => info.zswapped,
+            MemInfoField::Unevictable => info.unevictable,
+        }
+    }
+}
+
+/// `meminfo` entry: limits on one field. Percentages are of MemTotal.
+#[derive(Debug, Clone)]
+pub struct MemInfoLimit {
+    pub field: MemInfoField,
+    pub limit: UsageConfigParsed,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMINFO: &[u8] = b"MemTotal:       16318480 kB
+MemFree:         1043284 kB
+MemAvailable:    8210432 kB
+Buffers:          312040 kB
+SwapCached:        10240 kB
+SwapTotal:       8388604 kB
+SwapFree:        8000000 kB
+Zswap:               512 kB
+Zswapped:           2048 kB
+Dirty:               128 kB
+HugePages_Total:       0
+Hugepagesize:       2048 kB
+";
+
+    #[test]
+    fn parses_fields_in_bytes() {
+        let info = parse_meminfo(MEMINFO).unwrap();
+        assert_eq!(info.mem_total, 16318480 * 1024);
+        assert_eq!(info.mem_available, 8210432 * 1024);
+        assert_eq!(info.swap_total, 8388604 * 1024);
+        assert_eq!(info.swap_free, 8000000 * 1024);
+        assert_eq!(info.swap_cached, Some(10240 * 1024));
+        assert_eq!(info.zswap, Some(512 * 1024));
+        assert_eq!(info.zswapped, Some(2048 * 1024));
+        assert_eq!(info.dirty, Some(128 * 1024));
+    }
+
+    #[test]
+    fn unreported_fields_stay_none() {
+        let info = parse_meminfo(MEMINFO).unwrap();
+        assert_eq!(info.writeback, None);
+        assert_eq!(info.mlocked, None);
+        assert_eq!(MemInfoField::Unevictable.get(&info), None);
+        assert_eq!(MemInfoField::Dirty.get(&info), Some(128 * 1024));
+    }
+
+    #[test]
+    fn requires_total_and_available() {
+        assert!(parse_meminfo(b"MemTotal: 1000 kB\nMemFree: 10 kB\n").is_err());
+        assert!(parse_meminfo(b"").is_err());
+        // A truncated last line is skipped, not misread
+        let info = parse_meminfo(b"MemTotal: 1000 kB\nMemAvailable: 500 kB\nDirty:").unwrap();
+        assert_eq!(info.dirty, None);
+    }
+
+    #[test]
+    fn parse_number_reads_leading_digits() {
+        assert_eq!(parse_number(b"   42 kB"), Some(42));
+        assert_eq!(parse_number(b"7"), Some(7));
+        assert_eq!(parse_number(b" kB"), None);
+        assert_eq!(parse_number(b"99999999999999999999"), None);
+    }
+
+    #[test]
+    fn parses_stat() {
+        let stat = "1234 (sleep) S 1 1234 1234 0 -1 4194304 95 0 0 0 30 12 0 0 20 0 1 0 5000 5754880 \
+                    219 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 3 0 0 0 0 0\n";
+        let expected = ProcStat {
+            comm: "sleep",
+            state: 'S',
+            ppid: 1,
+            cpu_time: 42,
+            start_time: 5000,
+        };
+        assert_eq!(parse_stat(stat), Some(expected));
+    }
+
+    #[test]
+    fn stat_comm_ends_at_the_last_paren() {
+        let stat = "99 (a) b (c) Z 7 99 99 0 -1 0 0 0 0 0 7 8 0 0 20 0 1 0 300 0 0";
+        let parsed = parse_stat(stat).unwrap();
+        assert_eq!(
+            (parsed.comm, parsed.state, parsed.ppid),
+            ("a) b (c", 'Z', 7)
+        );
+        assert_eq!((parsed.cpu_time, parsed.start_time), (15, 300));
+        assert_eq!(parse_stat("99 (x) R 1 2 3"), None);
+        assert_eq!(parse_stat("99 (x)"), None);
+    }
+
+    #[test]
+    fn kb_field_matches_whole_keys() {
+        let status = b"Name:\tsleep\nVmSwapX:\t1 kB\nVmRSS:\t    2048 kB\nVmSwap:\t      16 kB\n";
+        assert_eq!(kb_field(status, b"VmSwap"), Some(16 * 1024));
+        assert_eq!(kb_field(status, b"VmRSS"), Some(2048 * 1024));
+        assert_eq!(kb_field(status, b"Name"), None);
+        assert_eq!(kb_field(status, b"VmHWM"), None);
+    }
+}
@@ -1,54 +1,126 @@
 use crate::{
-    config::{MemoryConfigParsed, RuntimeContext},
+    cgroup::headroom,
+    config::{KillHold, MemoryConfigParsed, RuntimeContext, UsageConfigParsed},
     events::{LogLevel, SentinelEvent},
+    killer::Relief,
     logging::{self, get_log_level},
-    psi::read_psi_total,
+    meminfo::{MEMINFO_PATH, MemInfo, MemInfoField, MemInfoReader},
+    psi::{
+        PSI_MEMORY_PATH, PollSet, PsiConfigParsed, PsiMetric, PsiSource, PsiVerdict,
+        wait_for_triggers,
+    },
+    swap::{SWAPS_PATH, SwapReader, SwapUsage},
 };
-use std::time::Instant;
-use sysinfo::{MemoryRefreshKind, RefreshKind, System};
+use std::os::fd::BorrowedFd;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+// Ring buffer size for rate-of-decline estimates (TREND_SAMPLES checks back)
+const TREND_SAMPLES: usize = 8;
+const TREND_MIN_SAMPLES: usize = 3;
 
 pub struct Monitor {
-    system: System,
-    last_psi_total: Option<u64>,
-    last_psi_time: Instant,
+    meminfo_reader: Option<MemInfoReader>, // None if /proc/meminfo could not be opened
+    swap_reader: Option<SwapReader>,
+    psi: PsiSource,
+    cgroups: Vec<PsiSource>, // One per ctx.cgroups entry (its memory.pressure)
+    poll: PollSet,
+    ram_gate: KillGate,
+    swap_gate: KillGate,
+    combined_gate: KillGate,
+    psi_gate: KillGate,
+    // Predictive triggers hold and re-arm like the static limits, on gates of their own
+    ram_predicted_gate: KillGate,
+    swap_predicted_gate: KillGate,
+    combined_predicted_gate: KillGate,
+    ram_trend: Trend,
+    swap_trend: Trend,
+    combined_trend: Trend,
+    compressed_swap_state: UsageState,
+    meminfo_state: Vec<UsageState>, // One per ctx.meminfo entry
+    cgroup_state: Vec<CgroupState>, // One per ctx.cgroups entry
     last_warn_time: Option<Instant>,
     pub ram_bytes: Option<u64>,
     pub ram_percent: Option<f64>,
     pub swap_bytes: Option<u64>,
     pub swap_percent: Option<f64>,
+    pub ram_exhaustion_eta_ms: Option<u64>, // At the recent rate of decline, until the kill limit
+    pub swap_exhaustion_eta_ms: Option<u64>,
+    pub combined_exhaustion_eta_ms: Option<u64>,
+    pub combined_bytes: Option<u64>,
+    pub combined_percent: Option<f64>,
     pub psi_pressure: Option<f64>,
+    pub psi_metric: Option<PsiMetric>,
+    pub meminfo: Option<MemInfo>, // Last successful read
+    pub swap_usage: Option<SwapUsage>,
 }
 
 pub enum MonitorStatus {
-    Normal,
-    Warn,                // Event emitted internally
-    Kill(SentinelEvent), // Main needs the event to decide/log (contains amount_needed)
+    Normal,                           // Every metric above its warn threshold
+    Warn,                             // Event emitted internally (unless debounced)
+    Kill(Box<SentinelEvent>, Relief), // The event (with amount_needed) and the pool to refill
 }
 
 impl Monitor {
     pub fn new() -> Self {
-        let mut system = System::new_with_specifics(
-            RefreshKind::nothing().with_memory(MemoryRefreshKind::everything()),
-        );
-        system.refresh_memory();
-
-        let total = Self::read_psi();
+        let meminfo_reader = MemInfoReader::new()
+            .map_err(|e| {
+                logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Error,
+                    text: format!("Cannot open {}: {}", MEMINFO_PATH, e),
+                })
+            })
+            .ok();
+        // Without it, swap is taken as reported (no zram accounting)
+        let swap_reader = SwapReader::new()
+            .map_err(|e| {
+                logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Warn,
+                    text: format!("Cannot open {}: {}", SWAPS_PATH, e),
+                })
+            })
+            .ok();
 
         Self {
-            system,
-            last_psi_total: total,
-            last_psi_time: Instant::now(),
+            meminfo_reader,
+            swap_reader,
+            psi: PsiSource::new(PathBuf::from(PSI_MEMORY_PATH)),
+            cgroups: Vec::new(),
+            poll: PollSet::new(),
+            ram_gate: KillGate::default(),
+            swap_gate: KillGate::default(),
+            combined_gate: KillGate::default(),
+            psi_gate: KillGate::default(),
+            ram_predicted_gate: KillGate::default(),
+            swap_predicted_gate: KillGate::default(),
+            combined_predicted_gate: KillGate::default(),
+            ram_trend: Trend::default(),
+            swap_trend: Trend::default(),
+            combined_trend: Trend::default(),
+            compressed_swap_state: UsageState::default(),
+            meminfo_state: Vec::new(),
+            cgroup_state: Vec::new(),
             last_warn_time: None,
             ram_bytes: None,
             ram_percent: None,
             swap_bytes: None,
             swap_percent: None,
+            ram_exhaustion_eta_ms: None,
+            swap_exhaustion_eta_ms: None,
+            combined_exhaustion_eta_ms: None,
+            combined_bytes: None,
+            combined_percent: None,
             psi_pressure: None,
+            psi_metric: None,
+            meminfo: None,
+            swap_usage: None,
         }
     }
 
     pub fn check(&mut self, ctx: &RuntimeContext) -> MonitorStatus {
-        self.system.refresh_memory();
+        self.refresh_meminfo();
+        let info = self.meminfo.unwrap_or_default(); // All zero (checks skipped) until a read works
+        let swap = self.refresh_swap(&info);
         let now = Instant::now();
 
         // We use this to store a warning from a higher priority check.
@@ -57,141 +129,321 @@ impl Monitor {
 
         // Priority 1: RAM (Hard Limit)
         if let Some(ram_config) = &ctx.ram {
-            let available = self.system.available_memory();
-            let total = self.system.total_memory();
+            let available = info.mem_available;
+            let total = info.mem_total;
 
             if total > 0 {
                 let percent_free = (available as f64 / total as f64) * 100.0;
                 self.ram_bytes = Some(available);
                 self.ram_percent = Some(percent_free);
+                self.ram_trend.push(now, available);
+                self.ram_exhaustion_eta_ms = self
+                    .ram_trend
+                    .eta_ms(available, kill_floor(ram_config, total));
 
-                if let Some((threshold, type_str)) =
-                    check_kill(ram_config, available, percent_free as f32)
-                {
-                    let amount_needed = calc_needed(ram_config, available, total);
-                    return MonitorStatus::Kill(SentinelEvent::KillTriggered {
-                        trigger: "LowMemory".to_string(),
-                        observed_value: if type_str == "bytes" {
-                            available as f64
-                        } else {
-                            percent_free
-                        },
-                        threshold_value: threshold,
-                        threshold_type: type_str,
-                        amount_needed,
-                    });
+                if let Some(event) = memory_kill_event(
+                    "LowMemory",
+                    &mut self.ram_gate,
+                    ram_config,
+                    available,
+                    total,
+                    None,
+                    now,
+                )
+                .or_else(|| {
+                    predicted_kill_event(
+                        "LowMemoryPredicted",
+                        (&self.ram_trend, &mut self.ram_predicted_gate),
+                        ram_config,
+                        available,
+                        total,
+                        None,
+                        now,
+                    )
+                }) {
+                    self.ram_trend.reset();
+                    return MonitorStatus::Kill(Box::new(event), Relief::Memory);
                 }
 
                 if let Some((threshold, type_str)) =
                     check_warn(ram_config, available, percent_free as f32)
+                    && pending_warn.is_none()
                 {
-                    if pending_warn.is_none() {
-                        pending_warn = Some(SentinelEvent::LowMemoryWarn {
-                            available_bytes: available,
-                            available_percent: percent_free,
-                            threshold_type: type_str,
-                            threshold_value: threshold,
-                        });
-                    }
+                    pending_warn = Some(SentinelEvent::LowMemoryWarn {
+                        available_bytes: available,
+                        available_percent: percent_free,
+                        threshold_type: type_str,
+                        threshold_value: threshold,
+                        cgroup: None,
+                    });
+                }
+                if let Some(event) = predicted_warn_event(
+                    "memory",
+                    &self.ram_trend,
+                    ram_config,
+                    available,
+                    total,
+                    None,
+                ) {
+                    pending_warn.get_or_insert(event);
                 }
             }
         }
 
         // Priority 2: Swap (Hard Limit)
         if let Some(swap_config) = &ctx.swap {
-            let free = self.system.free_swap();
-            let total = self.system.total_swap();
+            let free = swap.free;
+            let total = swap.total;
 
             if total > 0 {
                 let percent_free = (free as f64 / total as f64) * 100.0;
                 self.swap_bytes = Some(free);
                 self.swap_percent = Some(percent_free);
+                self.swap_trend.push(now, free);
+                self.swap_exhaustion_eta_ms =
+                    self.swap_trend.eta_ms(free, kill_floor(swap_config, total));
+
+                if let Some(event) = memory_kill_event(
+                    "LowSwap",
+                    &mut self.swap_gate,
+                    swap_config,
+                    free,
+                    total,
+                    None,
+                    now,
+                )
+                .or_else(|| {
+                    predicted_kill_event(
+                        "LowSwapPredicted",
+                        (&self.swap_trend, &mut self.swap_predicted_gate),
+                        swap_config,
+                        free,
+                        total,
+                        None,
+                        now,
+                    )
+                }) {
+                    self.swap_trend.reset();
+                    return MonitorStatus::Kill(Box::new(event), Relief::Swap);
+                }
 
                 if let Some((threshold, type_str)) =
-                    check_kill(swap_config, free, percent_free as f32)
+                    check_warn(swap_config, free, percent_free as f32)
+                    && pending_warn.is_none()
                 {
-                    let amount_needed = calc_needed(swap_config, free, total);
-                    return MonitorStatus::Kill(SentinelEvent::KillTriggered {
-                        trigger: "LowSwap".to_string(),
-                        observed_value: if type_str == "bytes" {
-                            free as f64
-                        } else {
-                            percent_free
-                        },
-                        threshold_value: threshold,
+                    pending_warn = Some(SentinelEvent::LowSwapWarn {
+                        free_bytes: free,
+                        raw_free_bytes: swap.raw_free,
+                        free_percent: percent_free,
                         threshold_type: type_str,
-                        amount_needed,
+                        threshold_value: threshold,
                     });
                 }
-
-                if let Some((threshold, type_str)) =
-                    check_warn(swap_config, free, percent_free as f32)
+                if let Some(event) =
+                    predicted_warn_event("swap", &self.swap_trend, swap_config, free, total, None)
                 {
-                    if pending_warn.is_none() {
-                        pending_warn = Some(SentinelEvent::LowSwapWarn {
-                            free_bytes: free,
-                            free_percent: percent_free,
-                            threshold_type: type_str,
-                            threshold_value: threshold,
-                        });
-                    }
+                    pending_warn.get_or_insert(event);
                 }
             }
         }
 
-        // Priority 3: PSI
-        if let Some(psi_config) = &ctx.psi {
-            if now.duration_since(self.last_psi_time).as_millis() as u64
-                >= psi_config.check_interval_ms
+        // Priority 3: RAM + Swap as one pool
+        if let Some(combined_config) = &ctx.combined
+            && let Some((free, total)) =
+                combined_pool(info.mem_available, info.mem_total, swap.free, swap.total)
+        {
+            let percent_free = (free as f64 / total as f64) * 100.0;
+            self.combined_bytes = Some(free);
+            self.combined_percent = Some(percent_free);
+            self.combined_trend.push(now, free);
+            self.combined_exhaustion_eta_ms = self
+                .combined_trend
+                .eta_ms(free, kill_floor(combined_config, total));
+
+            if let Some(event) = memory_kill_event(
+                "LowCombined",
+                &mut self.combined_gate,
+                combined_config,
+                free,
+                total,
+                None,
+                now,
+            )
+            .or_else(|| {
+                predicted_kill_event(
+                    "LowCombinedPredicted",
+                    (&self.combined_trend, &mut self.combined_predicted_gate),
+                    combined_config,
+                    free,
+                    total,
+                    None,
+                    now,
+                )
+            }) {
+                self.combined_trend.reset();
+                return MonitorStatus::Kill(Box::new(event), Relief::Combined);
+            }
+
+            if let Some((threshold, type_str)) =
+                check_warn(combined_config, free, percent_free as f32)
+                && pending_warn.is_none()
+            {
+                pending_warn = Some(SentinelEvent::LowCombinedWarn {
+                    free_bytes: free,
+                    free_percent: percent_free,
+                    threshold_type: type_str,
+                    threshold_value: threshold,
+                });
+            }
+            if let Some(event) = predicted_warn_event(
+                "memory + swap",
+                &self.combined_trend,
+                combined_config,
+                free,
+                total,
+                None,
+            ) {
+                pending_warn.get_or_insert(event);
+            }
+        }
+
+        // Priority 4: RAM held by compressed swap (zram + zswap)
+        if let Some(limit) = &ctx.compressed_swap
+            && info.mem_total > 0
+        {
+            let trigger = "HighCompressedSwap";
+            let used = swap.compressed_physical();
+            let state = &mut self.compressed_swap_state;
+            state.trend.push(now, info.mem_total.saturating_sub(used));
+            if let Some(event) =
+                usage_kill_event(trigger, &mut state.gate, limit, used, info.mem_total, now)
+            {
+                state.trend.reset();
+                return MonitorStatus::Kill(Box::new(event), Relief::Swap);
+            }
+            if let Some(event) =
+                usage_warn_event("compressed swap", &state.trend, limit, used, info.mem_total)
+            {
+                pending_warn.get_or_insert(event);
+            }
+        }
+
+        // Priority 5: Individual /proc/meminfo fields
+        self.sync_meminfo(ctx);
+        for (meminfo, state) in ctx.meminfo.iter().zip(self.meminfo_state.iter_mut()) {
+            let Some(value) = meminfo.field.get(&info) else {
+                continue; // Not reported by this kernel
+            };
+            let (name, limit) = (meminfo.field.as_str(), &meminfo.limit);
+            let trigger = meminfo.field.trigger();
+            state.trend.push(now, info.mem_total.saturating_sub(value));
+            if let Some(event) =
+                usage_kill_event(trigger, &mut state.gate, limit, value, info.mem_total, now)
+            {
+                state.trend.reset();
+                return MonitorStatus::Kill(Box::new(event), Relief::for_meminfo(meminfo.field));
+            }
+            if let Some(event) = usage_warn_event(name, &state.trend, limit, value, info.mem_total)
             {
-                if let Some(current_total) = Self::read_psi() {
-                    // We need previous data to calculate pressure
-                    if let Some(last_total) = self.last_psi_total {
-                        let time_delta_us =
-                            now.duration_since(self.last_psi_time).as_micros() as f64;
-                        let total_delta = (current_total.saturating_sub(last_total)) as f64;
-
-                        let pressure = if time_delta_us > 0.0 {
-                            (total_delta / time_delta_us) * 100.0
-                        } else {
-                            0.0
-                        };
-
-                        // Update State
-                        self.last_psi_total = Some(current_total);
-                        self.last_psi_time = now;
-                        self.psi_pressure = Some(pressure);
-
-                        // Check Kill
-                        if let Some(kill_max) = psi_config.kill_max_percent {
-                            if pressure as f32 > kill_max {
-                                let amount = psi_config.amount_to_free.expect("validated");
-                                return MonitorStatus::Kill(SentinelEvent::KillTriggered {
-                                    trigger: "PsiPressure".to_string(),
-                                    observed_value: pressure,
-                                    threshold_value: kill_max as f64,
-                                    threshold_type: "percent".to_string(),
-                                    amount_needed: Some(amount),
-                                });
-                            }
-                        }
-
-                        // Check Warn
-                        if pending_warn.is_none() {
-                            if let Some(warn_max) = psi_config.warn_max_percent {
-                                if pressure as f32 > warn_max {
-                                    pending_warn = Some(SentinelEvent::PsiPressureWarn {
-                                        pressure_curr: pressure,
-                                        threshold: warn_max as f64,
-                                    });
-                                }
-                            }
-                        }
-                    } else {
-                        self.last_psi_total = Some(current_total);
-                        self.last_psi_time = now;
-                    }
+                pending_warn.get_or_insert(event);
+            }
+        }
+
+        // Priority 6: PSI
+        let verdict = self.psi.evaluate(ctx.psi.as_ref(), now);
+        if let Some(event) = psi_kill_event(
+            "PsiPressure",
+            &mut self.psi_gate,
+            &verdict,
+            &self.psi,
+            ctx.psi.as_ref(),
+            None,
+            now,
+        ) {
+            return MonitorStatus::Kill(Box::new(event), Relief::Memory);
+        }
+        if let Some(event) = psi_warn_event(&verdict, None) {
+            pending_warn.get_or_insert(event);
+        }
+        self.psi_pressure = self.psi.pressure;
+        self.psi_metric = self.psi.metric;
+
+        // Priority 7: Scoped cgroups (memory.max headroom, then memory.pressure)
+        self.sync_cgroups(ctx);
+        let cgroups = ctx
+            .cgroups
+            .iter()
+            .zip(self.cgroups.iter_mut())
+            .zip(self.cgroup_state.iter_mut());
+        for ((cg, source), state) in cgroups {
+            if let Some(mem_config) = &cg.memory
+                && let Ok((current, Some(max))) = cg.files.read_usage()
+                && let Some((free, percent_free)) = headroom(current, max)
+            {
+                state.trend.push(now, free);
+
+                if let Some(event) = memory_kill_event(
+                    "CgroupLowMemory",
+                    &mut state.memory_gate,
+                    mem_config,
+                    free,
+                    max,
+                    Some(&cg.path),
+                    now,
+                )
+                .or_else(|| {
+                    predicted_kill_event(
+                        "CgroupLowMemoryPredicted",
+                        (&state.trend, &mut state.predicted_gate),
+                        mem_config,
+                        free,
+                        max,
+                        Some(&cg.path),
+                        now,
+                    )
+                }) {
+                    state.trend.reset();
+                    return MonitorStatus::Kill(Box::new(event), Relief::Memory);
                 }
+
+                if let Some((threshold, type_str)) =
+                    check_warn(mem_config, free, percent_free as f32)
+                    && pending_warn.is_none()
+                {
+                    pending_warn = Some(SentinelEvent::LowMemoryWarn {
+                        available_bytes: free,
+                        available_percent: percent_free,
+                        threshold_type: type_str,
+                        threshold_value: threshold,
+                        cgroup: Some(cg.path.clone()),
+                    });
+                }
+                if let Some(event) = predicted_warn_event(
+                    "memory",
+                    &state.trend,
+                    mem_config,
+                    free,
+                    max,
+                    Some(&cg.path),
+                ) {
+                    pending_warn.get_or_insert(event);
+                }
+            }
+
+            let verdict = source.evaluate(cg.psi.as_ref(), now);
+            if let Some(event) = psi_kill_event(
+                "CgroupPsiPressure",
+                &mut state.psi_gate,
+                &verdict,
+                source,
+                cg.psi.as_ref(),
+                Some(&cg.path),
+                now,
+            ) {
+                return MonitorStatus::Kill(Box::new(event), Relief::Memory);
+            }
+            if let Some(event) = psi_warn_event(&verdict, Some(&cg.path)) {
+                pending_warn.get_or_insert(event);
             }
         }
 
@@ -202,22 +454,112 @@ impl Monitor {
                 memory_available_percent: self.ram_percent,
                 swap_free_bytes: self.swap_bytes,
                 swap_free_percent: self.swap_percent,
+                memory_exhaustion_eta_ms: self.ram_exhaustion_eta_ms,
+                swap_exhaustion_eta_ms: self.swap_exhaustion_eta_ms,
+                combined_exhaustion_eta_ms: self.combined_exhaustion_eta_ms,
+                combined_free_bytes: self.combined_bytes,
+                combined_free_percent: self.combined_percent,
                 psi_pressure: self.psi_pressure,
+                psi_line: self.psi_metric.map(|m| m.line.as_str().to_string()),
+                psi_window: self.psi_metric.map(|m| m.window.as_str().to_string()),
+                meminfo: self.meminfo.map(Box::new),
+                swap_usage: self.swap_usage.map(Box::new),
             });
         }
 
-        // Final Decision (Warnings)
+        // Final Decision (Warnings). Still Warn when debounced: Normal means all metrics are healthy.
         if let Some(event) = pending_warn {
             if self.can_warn(ctx) {
                 logging::emit(&event);
                 self.last_warn_time = Some(now);
-                return MonitorStatus::Warn;
             }
+            return MonitorStatus::Warn;
         }
 
         MonitorStatus::Normal
     }
 
+    /// Sleeps until the next check, waking early if a kernel PSI trigger fires
+    /// or `wake` (the control socket) becomes readable. Returns true if a PSI trigger fired.
+    pub fn wait(&mut self, timeout: Duration, wake: Option<BorrowedFd<'_>>) -> bool {
+        wait_for_triggers(
+            &mut self.poll,
+            &mut self.psi,
+            &mut self.cgroups,
+            wake,
+            timeout,
+        )
+    }
+
+    // On failure the previous sample stays in use
+    fn refresh_meminfo(&mut self) {
+        let Some(reader) = &mut self.meminfo_reader else {
+            return;
+        };
+        match reader.read() {
+            Ok(info) => self.meminfo = Some(info),
+            Err(e) => logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Debug,
+                text: format!("Failed to read {}: {}", MEMINFO_PATH, e),
+            }),
+        }
+    }
+
+    // zram-aware swap figures; SwapFree/SwapTotal as reported if they can't be read
+    fn refresh_swap(&mut self, info: &MemInfo) -> SwapUsage {
+        let usage = match self.swap_reader.as_mut().map(|r| r.read(info)) {
+            Some(Ok(usage)) => usage,
+            Some(Err(e)) => {
+                logging::emit(&SentinelEvent::Message {
+                    level: LogLevel::Debug,
+                    text: format!("Failed to read {}: {}", SWAPS_PATH, e),
+                });
+                SwapUsage::unadjusted(info)
+            }
+            None => SwapUsage::unadjusted(info),
+        };
+        self.swap_usage = Some(usage);
+        usage
+    }
+
+    /// Keeps one PSI source per configured cgroup, rebuilding them when the list changes (reload).
+    fn sync_cgroups(&mut self, ctx: &RuntimeContext) {
+        let unchanged = self.cgroups.len() == ctx.cgroups.len()
+            && self
+                .cgroups
+                .iter()
+                .zip(&ctx.cgroups)
+                .all(|(source, cg)| source.path() == cg.dir.join("memory.pressure"));
+        if !unchanged {
+            self.cgroups = ctx
+                .cgroups
+                .iter()
+                .map(|cg| PsiSource::new(cg.dir.join("memory.pressure")))
+                .collect();
+            self.cgroup_state = ctx.cgroups.iter().map(|_| Default::default()).collect();
+        }
+    }
+
+    /// Keeps one `UsageState` per meminfo limit, starting over when the fields change (reload).
+    fn sync_meminfo(&mut self, ctx: &RuntimeContext) {
+        if self.meminfo_state.len() != ctx.meminfo.len()
+            || self
+                .meminfo_state
+                .iter()
+                .zip(&ctx.meminfo)
+                .any(|(state, m)| state.field != Some(m.field))
+        {
+            self.meminfo_state = ctx
+                .meminfo
+                .iter()
+                .map(|m| UsageState {
+                    field: Some(m.field),
+                    ..Default::default()
+                })
+                .collect();
+        }
+    }
+
     fn can_warn(&self, ctx: &RuntimeContext) -> bool {
         match self.last_warn_time {
             Some(last) => {
@@ -226,10 +568,364 @@ impl Monitor {
             None => true,
         }
     }
+}
+
+/// Per-cgroup counterparts of the system-wide gates and trend.
+#[derive(Default)]
+struct CgroupState {
+    memory_gate: KillGate,
+    psi_gate: KillGate,
+    predicted_gate: KillGate,
+    trend: Trend,
+}
 
-    fn read_psi() -> Option<u64> {
-        read_psi_total().ok()
+/// Gate and trend of one upper usage limit (`compressedSwap`, a `meminfo` field).
+#[derive(Default)]
+struct UsageState {
+    field: Option<MemInfoField>, // None for compressedSwap
+    gate: KillGate,
+    trend: Trend, // Of the room left below total RAM, so growing usage reads as a decline
+}
+
+/// The most recent free-memory samples of one pool, in a fixed ring buffer (no allocation).
+#[derive(Default)]
+struct Trend {
+    origin: Option<Instant>,
+    samples: [(f64, f64); TREND_SAMPLES], // (seconds since origin, free bytes)
+    len: usize,
+    next: usize,
+}
+
+impl Trend {
+    fn push(&mut self, now: Instant, free: u64) {
+        let origin = *self.origin.get_or_insert(now);
+        self.samples[self.next] = (now.duration_since(origin).as_secs_f64(), free as f64);
+        self.next = (self.next + 1) % TREND_SAMPLES;
+        self.len = (self.len + 1).min(TREND_SAMPLES);
     }
+
+    fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Least-squares slope in bytes per second, positive while free memory is shrinking.
+    fn decline_rate(&self) -> Option<f64> {
+        if self.len < TREND_MIN_SAMPLES {
+            return None;
+        }
+        let samples = &self.samples[..self.len];
+        let n = self.len as f64;
+        let mean_t = samples.iter().map(|(t, _)| t).sum::<f64>() / n;
+        let mean_b = samples.iter().map(|(_, b)| b).sum::<f64>() / n;
+        let (mut cov, mut var) = (0.0, 0.0);
+        for (t, b) in samples {
+            cov += (t - mean_t) * (b - mean_b);
+            var += (t - mean_t) * (t - mean_t);
+        }
+        if var <= 0.0 {
+            return None;
+        }
+        let rate = -cov / var;
+        (rate > 0.0).then_some(rate)
+    }
+
+    /// Milliseconds until `free` reaches `floor` at the current rate, None if not declining.
+    fn eta_ms(&self, free: u64, floor: u64) -> Option<u64> {
+        let rate = self.decline_rate()?;
+        let headroom = free.saturating_sub(floor) as f64;
+        Some((headroom / rate * 1000.0) as u64)
+    }
+}
+
+/// (free, total) of RAM and swap taken as one pool; None when there is nothing to measure.
+fn combined_pool(
+    mem_available: u64,
+    mem_total: u64,
+    swap_free: u64,
+    swap_total: u64,
+) -> Option<(u64, u64)> {
+    let total = mem_total + swap_total;
+    (total > 0).then_some((mem_available + swap_free, total))
+}
+
+// Where the pool counts as exhausted: the kill limit, or zero without one
+fn kill_floor(config: &MemoryConfigParsed, total: u64) -> u64 {
+    calc_needed(config, 0, total).unwrap_or(0)
+}
+
+/// Fires through `gate` like a static limit: the prediction must hold for `killAfter*`, and once
+/// it fired it re-arms only when exhaustion is no longer predicted (and free memory is back above
+/// the re-arm level, if one is set).
+fn predicted_kill_event(
+    trigger: &str,
+    (trend, gate): (&Trend, &mut KillGate),
+    config: &MemoryConfigParsed,
+    free: u64,
+    total: u64,
+    cgroup: Option<&str>,
+    now: Instant,
+) -> Option<SentinelEvent> {
+    let horizon = config.kill_when_exhausted_within_ms?;
+    let floor = kill_floor(config, total);
+    // Below the floor, the static kill limit (and its hold) is in charge
+    let eta = (free > floor)
+        .then(|| trend.eta_ms(free, floor))
+        .flatten()
+        .filter(|eta| *eta <= horizon);
+    let percent_free = (free as f64 / total as f64) * 100.0;
+    let recovered = eta.is_none() && check_rearm(config, free, percent_free as f32);
+    let held = gate.observe(eta.is_some(), recovered, &config.hold, now)?;
+    let eta = eta?;
+    // Enough to push exhaustion back beyond the horizon at the current rate
+    let rate = trend.decline_rate()?;
+    let needed = ((rate * horizon as f64 / 1000.0) as u64).saturating_sub(free - floor);
+    Some(SentinelEvent::KillTriggered {
+        trigger: trigger.to_string(),
+        observed_value: eta as f64,
+        threshold_value: horizon as f64,
+        threshold_type: "ms".to_string(),
+        amount_needed: Some(needed.max(1)),
+        psi_line: None,
+        psi_window: None,
+        cgroup: cgroup.map(str::to_string),
+        held_ms: held.ms,
+        held_samples: held.samples,
+    })
+}
+
+fn predicted_warn_event(
+    pool: &str,
+    trend: &Trend,
+    config: &MemoryConfigParsed,
+    free: u64,
+    total: u64,
+    cgroup: Option<&str>,
+) -> Option<SentinelEvent> {
+    let horizon = config.warn_when_exhausted_within_ms?;
+    let eta = trend
+        .eta_ms(free, kill_floor(config, total))
+        .filter(|eta| *eta <= horizon)?;
+    Some(SentinelEvent::ExhaustionPredicted {
+        pool: pool.to_string(),
+        free_bytes: free,
+        decline_bytes_per_sec: trend.decline_rate()? as u64,
+        eta_ms: eta,
+        horizon_ms: horizon,
+        cgroup: cgroup.map(str::to_string),
+    })
+}
+
+/// Hysteresis for one kill trigger: a breach fires only after it has held for `KillHold`,
+/// and a trigger that fired stays disarmed until its metric recovers past the re-arm level.
+struct KillGate {
+    breach_since: Option<Instant>,
+    samples: u32,
+    armed: bool,
+}
+
+impl Default for KillGate {
+    fn default() -> Self {
+        Self {
+            breach_since: None,
+            samples: 0,
+            armed: true,
+        }
+    }
+}
+
+/// How long a kill condition held before it fired.
+struct Held {
+    ms: u64,
+    samples: u32,
+}
+
+impl KillGate {
+    /// Records one sample. `recovered` re-arms the gate (always true without a re-arm threshold).
+    /// Returns Some once the breach has held long enough; the hold then starts over.
+    fn observe(
+        &mut self,
+        breached: bool,
+        recovered: bool,
+        hold: &KillHold,
+        now: Instant,
+    ) -> Option<Held> {
+        if recovered {
+            self.armed = true;
+        }
+        if !breached {
+            self.breach_since = None;
+            self.samples = 0;
+            return None;
+        }
+        let since = *self.breach_since.get_or_insert(now);
+        self.samples += 1;
+        let held = Held {
+            ms: now.duration_since(since).as_millis() as u64,
+            samples: self.samples,
+        };
+        if !self.armed || !hold.satisfied(held.samples, held.ms) {
+            return None;
+        }
+        self.armed = false;
+        self.breach_since = None;
+        self.samples = 0;
+        Some(held)
+    }
+}
+
+fn memory_kill_event(
+    trigger: &str,
+    gate: &mut KillGate,
+    config: &MemoryConfigParsed,
+    free: u64,
+    total: u64,
+    cgroup: Option<&str>,
+    now: Instant,
+) -> Option<SentinelEvent> {
+    let percent_free = (free as f64 / total as f64) * 100.0;
+    let kill = check_kill(config, free, percent_free as f32);
+    let recovered = check_rearm(config, free, percent_free as f32);
+    let held = gate.observe(kill.is_some(), recovered, &config.hold, now)?;
+    let (threshold, type_str) = kill?;
+    Some(SentinelEvent::KillTriggered {
+        trigger: trigger.to_string(),
+        observed_value: if type_str == "bytes" {
+            free as f64
+        } else {
+            percent_free
+        },
+        threshold_value: threshold,
+        threshold_type: type_str,
+        amount_needed: calc_needed(config, free, total),
+        psi_line: None,
+        psi_window: None,
+        cgroup: cgroup.map(str::to_string),
+        held_ms: held.ms,
+        held_samples: held.samples,
+    })
+}
+
+/// Fires through the limit's own gate once usage has exceeded the kill limit for its hold.
+fn usage_kill_event(
+    trigger: &str,
+    gate: &mut KillGate,
+    limit: &UsageConfigParsed,
+    value: u64,
+    total: u64,
+    now: Instant,
+) -> Option<SentinelEvent> {
+    let kill = limit.kill_limit(total).filter(|(kill, _)| value > *kill);
+    let recovered = limit.rearmed(value, total);
+    let held = gate.observe(kill.is_some(), recovered, &limit.hold, now)?;
+    let (kill, type_str) = kill?;
+    let percent = value as f64 / total as f64 * 100.0;
+    Some(SentinelEvent::KillTriggered {
+        trigger: trigger.to_string(),
+        observed_value: if type_str == "bytes" {
+            value as f64
+        } else {
+            percent
+        },
+        threshold_value: if type_str == "bytes" {
+            kill as f64
+        } else {
+            limit.kill_max_percent.unwrap_or_default() as f64
+        },
+        threshold_type: type_str.to_string(),
+        amount_needed: Some(value - kill),
+        psi_line: None,
+        psi_window: None,
+        cgroup: None,
+        held_ms: held.ms,
+        held_samples: held.samples,
+    })
+}
+
+// `trend` tracks the room left below `total`; its ETA is until the kill limit is reached
+fn usage_warn_event(
+    metric: &str,
+    trend: &Trend,
+    limit: &UsageConfigParsed,
+    value: u64,
+    total: u64,
+) -> Option<SentinelEvent> {
+    let (warn, type_str) = limit.warn_limit(total).filter(|(warn, _)| value > *warn)?;
+    let eta_ms = limit.kill_limit(total).and_then(|(kill, _)| {
+        trend.eta_ms(total.saturating_sub(value), total.saturating_sub(kill))
+    });
+    Some(SentinelEvent::HighUsageWarn {
+        metric: metric.to_string(),
+        value_bytes: value,
+        value_percent: value as f64 / total as f64 * 100.0,
+        threshold_type: type_str.to_string(),
+        threshold_value: if type_str == "bytes" {
+            warn as f64
+        } else {
+            limit.warn_max_percent.unwrap_or_default() as f64
+        },
+        eta_ms,
+    })
+}
+
+fn psi_kill_event(
+    trigger: &str,
+    gate: &mut KillGate,
+    verdict: &PsiVerdict,
+    source: &PsiSource,
+    config: Option<&PsiConfigParsed>,
+    cgroup: Option<&str>,
+    now: Instant,
+) -> Option<SentinelEvent> {
+    let config = config?;
+    let kill_max = config.kill_max_percent?;
+    let fired = match verdict {
+        PsiVerdict::Kill { pressure, .. } => Some(*pressure),
+        _ => None,
+    };
+    // Kernel triggers serve the kill itself; samples tell whether the breach is still going on
+    let sampled = source.kill_pressure;
+    let pressure = fired.or(sampled)?; // No new information this time
+    let breached = fired.is_some() || pressure as f32 > kill_max;
+    let recovered = config
+        .rearm_max_percent
+        .is_none_or(|rearm| sampled.is_some_and(|p| (p as f32) < rearm));
+    let held = gate.observe(breached, recovered, &config.hold, now)?;
+    Some(SentinelEvent::KillTriggered {
+        trigger: trigger.to_string(),
+        observed_value: pressure,
+        threshold_value: kill_max as f64,
+        threshold_type: "percent".to_string(),
+        amount_needed: Some(config.amount_to_free.expect("validated")),
+        psi_line: Some(config.kill_metric.line.as_str().to_string()),
+        psi_window: Some(config.kill_metric.window.as_str().to_string()),
+        cgroup: cgroup.map(str::to_string),
+        held_ms: held.ms,
+        held_samples: held.samples,
+    })
+}
+
+// A kill verdict held back by its gate is still worth a warning
+fn psi_warn_event(verdict: &PsiVerdict, cgroup: Option<&str>) -> Option<SentinelEvent> {
+    let (PsiVerdict::Kill {
+        pressure,
+        threshold,
+        metric,
+    }
+    | PsiVerdict::Warn {
+        pressure,
+        threshold,
+        metric,
+    }) = verdict
+    else {
+        return None;
+    };
+    Some(SentinelEvent::PsiPressureWarn {
+        pressure_curr: *pressure,
+        threshold: *threshold as f64,
+        line: metric.line.as_str().to_string(),
+        window: metric.window.as_str().to_string(),
+        cgroup: cgroup.map(str::to_string),
+    })
 }
 
 // Helpers returning (threshold_val, type_string)
@@ -246,10 +942,10 @@ fn check_kill(
         // Task said: "If a byte limit is set, the percentage limit is ignored".
         return None;
     }
-    if let Some(limit_percent) = config.kill_min_free_percent {
-        if free_percent < limit_percent {
-            return Some((limit_percent as f64, "percent".to_string()));
-        }
+    if let Some(limit_percent) = config.kill_min_free_percent
+        && free_percent < limit_percent
+    {
+        return Some((limit_percent as f64, "percent".to_string()));
     }
     None
 }
@@ -265,14 +961,24 @@ fn check_warn(
         }
         return None;
     }
-    if let Some(limit_percent) = config.warn_min_free_percent {
-        if free_percent < limit_percent {
-            return Some((limit_percent as f64, "percent".to_string()));
-        }
+    if let Some(limit_percent) = config.warn_min_free_percent
+        && free_percent < limit_percent
+    {
+        return Some((limit_percent as f64, "percent".to_string()));
     }
     None
 }
 
+// True once free memory is back above the re-arm threshold, or always if none is set
+fn check_rearm(config: &MemoryConfigParsed, free_bytes: u64, free_percent: f32) -> bool {
+    if let Some(limit) = config.rearm_min_free_bytes {
+        return free_bytes >= limit;
+    }
+    config
+        .rearm_min_free_percent
+        .is_none_or(|limit| free_percent >= limit)
+}
+
 fn calc_needed(config: &MemoryConfigParsed, current_free: u64, total: u64) -> Option<u64> {
     let target = if let Some(bytes) = config.kill_min_free_bytes {
         bytes
@@ -288,3 +994,297 @@ fn calc_needed(config: &MemoryConfigParsed, current_free: u64, total: u64) -> Op
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MemoryConfig, UsageConfig};
+
+    fn hold(samples: u32, ms: u64) -> KillHold {
+        KillHold::new(Some(samples), Some(ms))
+    }
+
+    #[test]
+    fn gate_without_hold_fires_on_first_breach() {
+        let mut gate = KillGate::default();
+        let now = Instant::now();
+        assert!(
+            gate.observe(false, true, &KillHold::new(None, None), now)
+                .is_none()
+        );
+        let held = gate
+            .observe(true, true, &KillHold::new(None, None), now)
+            .unwrap();
+        assert_eq!((held.samples, held.ms), (1, 0));
+    }
+
+    #[test]
+    fn gate_waits_for_samples_and_duration() {
+        let mut gate = KillGate::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let hold = hold(3, 1500);
+        assert!(gate.observe(true, true, &hold, at(0)).is_none());
+        assert!(gate.observe(true, true, &hold, at(1000)).is_none());
+        // Third sample, but only 1s in
+        assert!(gate.observe(true, true, &hold, at(1000)).is_none());
+        let held = gate.observe(true, true, &hold, at(2000)).unwrap();
+        assert_eq!((held.samples, held.ms), (4, 2000));
+    }
+
+    #[test]
+    fn gate_restarts_hold_when_breach_ends() {
+        let mut gate = KillGate::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let hold = hold(2, 0);
+        assert!(gate.observe(true, true, &hold, at(0)).is_none());
+        assert!(gate.observe(false, true, &hold, at(100)).is_none());
+        assert!(gate.observe(true, true, &hold, at(200)).is_none());
+        let held = gate.observe(true, true, &hold, at(300)).unwrap();
+        assert_eq!((held.samples, held.ms), (2, 100));
+    }
+
+    #[test]
+    fn gate_stays_disarmed_until_recovered() {
+        let mut gate = KillGate::default();
+        let now = Instant::now();
+        let hold = KillHold::new(None, None);
+        assert!(gate.observe(true, false, &hold, now).is_some());
+        // Still breached, or no longer breached but not recovered: no new kill
+        assert!(gate.observe(true, false, &hold, now).is_none());
+        assert!(gate.observe(false, false, &hold, now).is_none());
+        assert!(gate.observe(true, false, &hold, now).is_none());
+        // Recovery re-arms, even on a sample that breaches again
+        assert!(gate.observe(true, true, &hold, now).is_some());
+    }
+
+    const MB: u64 = 1_000_000;
+
+    // `free` sampled once per second
+    fn trend(free: &[u64]) -> (Trend, Instant) {
+        let mut trend = Trend::default();
+        let start = Instant::now();
+        let mut now = start;
+        for (i, &f) in free.iter().enumerate() {
+            now = start + Duration::from_secs(i as u64);
+            trend.push(now, f);
+        }
+        (trend, now)
+    }
+
+    #[test]
+    fn trend_needs_min_samples() {
+        let (trend, _) = trend(&[1000 * MB, 900 * MB]);
+        assert_eq!(trend.decline_rate(), None);
+        assert_eq!(trend.eta_ms(900 * MB, 0), None);
+    }
+
+    #[test]
+    fn trend_slope_and_eta() {
+        let (trend, _) = trend(&[1000 * MB, 900 * MB, 800 * MB, 700 * MB]);
+        let rate = trend.decline_rate().unwrap();
+        assert!((rate - 100.0 * MB as f64).abs() < 1.0);
+        assert_eq!(trend.eta_ms(700 * MB, 200 * MB), Some(5000));
+        assert_eq!(trend.eta_ms(100 * MB, 200 * MB), Some(0));
+    }
+
+    #[test]
+    fn trend_ignores_growth_and_flat_lines() {
+        let (growing, _) = trend(&[100 * MB, 200 * MB, 300 * MB]);
+        assert_eq!(growing.decline_rate(), None);
+        let (flat, _) = trend(&[100 * MB; 4]);
+        assert_eq!(flat.decline_rate(), None);
+    }
+
+    #[test]
+    fn trend_keeps_only_recent_samples() {
+        // A long rise, then a steady decline filling the whole ring buffer
+        let mut free: Vec<u64> = (0..20).map(|i| i * 100 * MB).collect();
+        free.extend((0..TREND_SAMPLES as u64).map(|i| 5000 * MB - i * 50 * MB));
+        let (trend, _) = trend(&free);
+        let rate = trend.decline_rate().unwrap();
+        assert!((rate - 50.0 * MB as f64).abs() < 1.0);
+    }
+
+    #[test]
+    fn prediction_holds_and_rearms_through_its_gate() {
+        let config = MemoryConfigParsed::try_from_config(
+            MemoryConfig {
+                kill_after_samples: Some(2),
+                kill_when_exhausted_within_ms: Some(20_000),
+                ..Default::default()
+            },
+            "ram",
+            None,
+        )
+        .unwrap();
+        let mut gate = KillGate::default();
+        let (declining, now) = trend(&[1000 * MB, 900 * MB, 800 * MB]);
+        let mut predict = |trend: &Trend, free, now| {
+            predicted_kill_event(
+                "P",
+                (trend, &mut gate),
+                &config,
+                free,
+                10_000 * MB,
+                None,
+                now,
+            )
+        };
+        // 8s to exhaustion is within the horizon, but the hold needs a second sample
+        assert!(predict(&declining, 800 * MB, now).is_none());
+        let event = predict(&declining, 800 * MB, now).unwrap();
+        let SentinelEvent::KillTriggered { held_samples, .. } = event else {
+            panic!("not a kill");
+        };
+        assert_eq!(held_samples, 2);
+        // Disarmed while exhaustion is still predicted...
+        assert!(predict(&declining, 800 * MB, now).is_none());
+        assert!(predict(&declining, 800 * MB, now).is_none());
+        // ...and re-armed once it no longer is
+        assert!(predict(&Trend::default(), 800 * MB, now).is_none());
+        assert!(predict(&declining, 800 * MB, now).is_none());
+        assert!(predict(&declining, 800 * MB, now).is_some());
+    }
+
+    const GB: u64 = 1_000_000_000;
+
+    #[test]
+    fn combined_limit_counts_ram_and_swap_together() {
+        let config = MemoryConfigParsed::try_from_config(
+            MemoryConfig {
+                kill_min_free_percent: Some(10.0),
+                ..Default::default()
+            },
+            "combined",
+            None,
+        )
+        .unwrap();
+        let now = Instant::now();
+        let check = |available, swap_free| {
+            let (free, total) = combined_pool(available, 16 * GB, swap_free, 8 * GB).unwrap();
+            memory_kill_event(
+                "LowCombined",
+                &mut KillGate::default(),
+                &config,
+                free,
+                total,
+                None,
+                now,
+            )
+        };
+        // 1GB of RAM is under 10% on its own, but 3GB of 24GB is 12.5%
+        assert!(check(GB, 2 * GB).is_none());
+        // 2GB of 24GB: 8.3%, and 0.4GB short of the limit
+        let Some(SentinelEvent::KillTriggered {
+            observed_value,
+            amount_needed,
+            ..
+        }) = check(GB, GB)
+        else {
+            panic!("not a kill");
+        };
+        assert!((observed_value - 100.0 / 12.0).abs() < 1e-9);
+        assert_eq!(amount_needed, Some(GB * 2 / 5));
+    }
+
+    #[test]
+    fn combined_eta_runs_to_the_combined_kill_limit() {
+        let config = MemoryConfigParsed::try_from_config(
+            MemoryConfig {
+                kill_min_free_percent: Some(10.0),
+                ..Default::default()
+            },
+            "combined",
+            None,
+        )
+        .unwrap();
+        // RAM drains at 100MB/s while 2GB of swap stays free: 4GB of 24GB left, limit at 2.4GB
+        let free: Vec<u64> = (0..4)
+            .map(|i| {
+                combined_pool(2 * GB - i * 100 * MB, 16 * GB, 2 * GB, 8 * GB)
+                    .unwrap()
+                    .0
+            })
+            .collect();
+        let (trend, _) = trend(&free);
+        let (now_free, total) = combined_pool(17 * GB / 10, 16 * GB, 2 * GB, 8 * GB).unwrap();
+        assert_eq!(now_free, 3700 * MB);
+        assert_eq!(
+            trend.eta_ms(now_free, kill_floor(&config, total)),
+            Some(13_000)
+        );
+    }
+
+    #[test]
+    fn combined_pool_without_memory_is_skipped() {
+        assert_eq!(combined_pool(0, 0, 0, 0), None);
+        assert_eq!(combined_pool(GB, 4 * GB, 0, 0), Some((GB, 4 * GB)));
+    }
+
+    #[test]
+    fn usage_limit_holds_and_rearms() {
+        let limit = UsageConfigParsed::try_from_config(
+            UsageConfig {
+                kill_max_bytes: Some("500M".to_string()),
+                kill_after_samples: Some(2),
+                rearm_max_bytes: Some("300M".to_string()),
+                ..Default::default()
+            },
+            "meminfo.shmem",
+            10_000 * MB,
+        )
+        .unwrap();
+        let mut gate = KillGate::default();
+        let now = Instant::now();
+        let mut check =
+            |value| usage_kill_event("HighShmem", &mut gate, &limit, value, 10_000 * MB, now);
+        // One noisy sample doesn't kill
+        assert!(check(600 * MB).is_none());
+        assert!(check(400 * MB).is_none());
+        assert!(check(600 * MB).is_none());
+        let Some(SentinelEvent::KillTriggered {
+            amount_needed,
+            held_samples,
+            ..
+        }) = check(600 * MB)
+        else {
+            panic!("not a kill");
+        };
+        assert_eq!((amount_needed, held_samples), (Some(100 * MB), 2));
+        // Below the kill limit but above the re-arm limit: stays disarmed
+        assert!(check(400 * MB).is_none());
+        assert!(check(600 * MB).is_none());
+        assert!(check(600 * MB).is_none());
+        assert!(check(200 * MB).is_none());
+        assert!(check(600 * MB).is_none());
+        assert!(check(600 * MB).is_some());
+    }
+
+    #[test]
+    fn usage_warning_reports_time_to_kill_limit() {
+        let limit = UsageConfigParsed::try_from_config(
+            UsageConfig {
+                warn_max_bytes: Some("300M".to_string()),
+                kill_max_bytes: Some("1G".to_string()),
+                ..Default::default()
+            },
+            "compressedSwap",
+            10_000 * MB,
+        )
+        .unwrap();
+        // Usage growing by 100MB/s, now at 500MB
+        let room: Vec<u64> = [200, 300, 400, 500]
+            .iter()
+            .map(|u| (10_000 - u) * MB)
+            .collect();
+        let (trend, _) = trend(&room);
+        let Some(SentinelEvent::HighUsageWarn { eta_ms, .. }) =
+            usage_warn_event("compressed swap", &trend, &limit, 500 * MB, 10_000 * MB)
+        else {
+            panic!("no warning");
+        };
+        assert_eq!(eta_ms, Some(5000));
+    }
+}
@@ -0,0 +1,96 @@
+use nix::errno::Errno;
+use nix::sys::signal::Signal;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::time::{Duration, Instant};
+
+/// A process file descriptor (Linux 5.3+).
+/// Unlike a raw PID, it keeps referring to the same process even after that process exits
+/// and its PID is recycled, so signals sent through it can never hit an unrelated process.
+pub struct PidFd(OwnedFd);
+
+impl PidFd {
+    /// Returns `ENOSYS` on kernels without pidfd support and `ESRCH` if the process is gone.
+    pub fn open(pid: u32) -> nix::Result<Self> {
+        // Safety: plain syscall, no pointers involved. On success the kernel hands us a new fd we own.
+        let res =
+            unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0 as libc::c_uint) };
+        let fd = Errno::result(res)?;
+        Ok(Self(unsafe { OwnedFd::from_raw_fd(fd as i32) }))
+    }
+
+    pub fn send_signal(&self, signal: Signal) -> nix::Result<()> {
+        // Safety: a NULL siginfo is explicitly allowed (equivalent to kill(2)).
+        let res = unsafe {
+            libc::syscall(
+                libc::SYS_pidfd_send_signal,
+                self.0.as_raw_fd(),
+                signal as libc::c_int,
+                std::ptr::null::<libc::siginfo_t>(),
+                0 as libc::c_uint,
+            )
+        };
+        Errno::result(res).map(drop)
+    }
+
+    /// Reaps the address space of a process that is already dying (SIGKILLed) right away,
+    /// instead of waiting for its own exit path to tear it down (Linux 5.15+).
+    pub fn mrelease(&self) -> nix::Result<()> {
+        let res = unsafe {
+            libc::syscall(
+                libc::SYS_process_mrelease,
+                self.0.as_raw_fd(),
+                0 as libc::c_uint,
+            )
+        };
+        Errno::result(res).map(drop)
+    }
+
+    /// Blocks until the process terminates (the pidfd becomes readable) or `timeout` elapses.
+    /// Returns true if the process exited.
+    pub fn wait_exit(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let mut pfd = libc::pollfd {
+                fd: self.0.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            };
+            let timeout_ms = remaining.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+            let res = unsafe { libc::poll(&mut pfd, 1, timeout_ms) };
+            if res > 0 {
+                return (pfd.revents & libc::POLLIN) != 0;
+            }
+            if res == 0 || Errno::last() != Errno::EINTR {
+                return false;
+            }
+            // EINTR (e.g. SIGHUP for reload): keep waiting for the remaining time
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn pidfd_tracks_exit_after_signal() {
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let fd = PidFd::open(child.id()).unwrap();
+        assert!(!fd.wait_exit(Duration::ZERO));
+        fd.send_signal(Signal::SIGKILL).unwrap();
+        child.wait().unwrap();
+        assert!(fd.wait_exit(Duration::ZERO));
+        // Reaped: the pidfd still refers to the dead process, never to a recycled PID
+        assert_eq!(fd.send_signal(Signal::SIGKILL), Err(Errno::ESRCH));
+    }
+
+    #[test]
+    fn open_reaped_pid_is_esrch() {
+        let mut child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        assert!(matches!(PidFd::open(pid), Err(Errno::ESRCH)));
+    }
+}
@@ -1,16 +1,25 @@
+use crate::config::KillHold;
+use crate::events::{LogLevel, SentinelEvent};
+use crate::logging;
 use crate::utils::parse_size;
 use serde::{Deserialize, Serialize};
 use std::error::Error;
 use std::fmt;
-use std::fs;
-use std::io;
-use std::num::ParseIntError;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::num::{ParseFloatError, ParseIntError};
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
 #[derive(Debug)]
 pub enum PsiError {
     Io(io::Error),
-    FieldNotFound,
+    FieldNotFound(&'static str),
     Parse(ParseIntError),
+    ParseFloat(ParseFloatError),
     ValidationError(String), // New variant for validation errors
 }
 
@@ -18,8 +27,9 @@ impl fmt::Display for PsiError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             PsiError::Io(e) => write!(f, "Filesystem access error: {}", e),
-            PsiError::FieldNotFound => write!(f, "PSI field 'some total=' was not found."),
+            PsiError::FieldNotFound(field) => write!(f, "PSI field '{}' was not found.", field),
             PsiError::Parse(e) => write!(f, "Value parsing error: {}", e),
+            PsiError::ParseFloat(e) => write!(f, "Value parsing error: {}", e),
             PsiError::ValidationError(msg) => write!(f, "Configuration validation error: {}", msg),
         }
     }
@@ -30,7 +40,8 @@ impl Error for PsiError {
         match self {
             PsiError::Io(e) => Some(e),
             PsiError::Parse(e) => Some(e),
-            PsiError::FieldNotFound => None,
+            PsiError::ParseFloat(e) => Some(e),
+            PsiError::FieldNotFound(_) => None,
             PsiError::ValidationError(_) => None,
         }
     }
@@ -48,29 +59,172 @@ impl From<ParseIntError> for PsiError {
     }
 }
 
-pub fn read_psi_total() -> Result<u64, PsiError> {
-    let content = fs::read_to_string("/proc/pressure/memory")?;
+impl From<ParseFloatError> for PsiError {
+    fn from(err: ParseFloatError) -> PsiError {
+        PsiError::ParseFloat(err)
+    }
+}
+
+pub const PSI_MEMORY_PATH: &str = "/proc/pressure/memory";
+
+// Kernel limits for trigger windows
+const TRIGGER_WINDOW_MIN_MS: u64 = 500;
+const TRIGGER_WINDOW_MAX_MS: u64 = 10_000;
+// Unprivileged triggers must use a window that is a multiple of 2s
+const TRIGGER_WINDOW_UNPRIVILEGED_STEP_MS: u64 = 2_000;
 
+/// One line ("some" or "full") of /proc/pressure/memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PsiLineStats {
+    pub avg10: f64,
+    pub avg60: f64,
+    pub avg300: f64,
+    pub total: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PsiSnapshot {
+    pub some: PsiLineStats,
+    pub full: Option<PsiLineStats>, // Absent on very old kernels
+}
+
+impl PsiSnapshot {
+    pub fn line(&self, line: PsiLine) -> Option<&PsiLineStats> {
+        match line {
+            PsiLine::Some => Some(&self.some),
+            PsiLine::Full => self.full.as_ref(),
+        }
+    }
+}
+
+/// Reads a pressure file: /proc/pressure/memory or a cgroup's memory.pressure.
+pub fn read_psi(path: &Path) -> Result<PsiSnapshot, PsiError> {
+    parse_psi(&fs::read_to_string(path)?)
+}
+
+fn parse_psi(content: &str) -> Result<PsiSnapshot, PsiError> {
+    let mut some = None;
+    let mut full = None;
     for line in content.lines() {
-        if line.starts_with("some") {
-            for part in line.split_whitespace() {
-                if let Some(val_str) = part.strip_prefix("total=") {
-                    return Ok(val_str.parse::<u64>()?);
-                }
-            }
+        if let Some(rest) = line.strip_prefix("some ") {
+            some = Some(parse_psi_line(rest)?);
+        } else if let Some(rest) = line.strip_prefix("full ") {
+            full = Some(parse_psi_line(rest)?);
+        }
+    }
+
+    Ok(PsiSnapshot {
+        some: some.ok_or(PsiError::FieldNotFound("some"))?,
+        full,
+    })
+}
+
+// Format: "avg10=0.00 avg60=0.00 avg300=0.00 total=0"
+fn parse_psi_line(rest: &str) -> Result<PsiLineStats, PsiError> {
+    let mut stats = PsiLineStats::default();
+    let mut has_total = false;
+    for part in rest.split_whitespace() {
+        if let Some(v) = part.strip_prefix("avg10=") {
+            stats.avg10 = v.parse()?;
+        } else if let Some(v) = part.strip_prefix("avg60=") {
+            stats.avg60 = v.parse()?;
+        } else if let Some(v) = part.strip_prefix("avg300=") {
+            stats.avg300 = v.parse()?;
+        } else if let Some(v) = part.strip_prefix("total=") {
+            stats.total = v.parse()?;
+            has_total = true;
         }
     }
-    Err(PsiError::FieldNotFound)
+    if !has_total {
+        return Err(PsiError::FieldNotFound("total="));
+    }
+    Ok(stats)
 }
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct PsiConfig {
     pub warn_max_percent: Option<f32>,
     pub kill_max_percent: Option<f32>,
     pub amount_to_free: Option<String>,
     pub check_interval_ms: Option<u64>,
+    pub mode: Option<PsiMode>,
+    pub warn_line: Option<PsiLine>,
+    pub warn_window: Option<PsiWindow>,
+    pub kill_line: Option<PsiLine>,
+    pub kill_window: Option<PsiWindow>,
+    /// Kill only once pressure stayed above `killMaxPercent` for this many samples / this long.
+    pub kill_after_samples: Option<u32>,
+    pub kill_after_ms: Option<u64>,
+    /// After a kill, the trigger stays disarmed until pressure drops below this.
+    pub rearm_max_percent: Option<f32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PsiMode {
+    /// Register kernel triggers and wake up as soon as a threshold is breached.
+    /// Falls back to `Sample` when triggers are not permitted.
+    Trigger,
+    /// Compute pressure from `total=` deltas every `checkIntervalMs`.
+    Sample,
 }
+
+/// "some": at least one task stalled. "full": all non-idle tasks stalled at once.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PsiLine {
+    Some,
+    Full,
+}
+
+impl PsiLine {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PsiLine::Some => "some",
+            PsiLine::Full => "full",
+        }
+    }
+}
+
+/// Which figure a threshold is compared against.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PsiWindow {
+    /// Our own computation from `total=` deltas over `checkIntervalMs` (or a kernel trigger).
+    Total,
+    /// The kernel's running averages.
+    Avg10,
+    Avg60,
+    Avg300,
+}
+
+impl PsiWindow {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PsiWindow::Total => "total",
+            PsiWindow::Avg10 => "avg10",
+            PsiWindow::Avg60 => "avg60",
+            PsiWindow::Avg300 => "avg300",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PsiMetric {
+    pub line: PsiLine,
+    pub window: PsiWindow,
+}
+
+impl PsiMetric {
+    fn from_config(line: Option<PsiLine>, window: Option<PsiWindow>) -> Self {
+        Self {
+            line: line.unwrap_or(PsiLine::Some),
+            window: window.unwrap_or(PsiWindow::Total),
+        }
+    }
+}
+
 impl PsiConfig {
     pub fn is_effectively_empty(&self) -> bool {
         self.warn_max_percent.is_none() && self.kill_max_percent.is_none()
@@ -82,25 +236,39 @@ pub struct PsiConfigParsed {
     pub kill_max_percent: Option<f32>,
     pub amount_to_free: Option<u64>,
     pub check_interval_ms: u64,
+    pub mode: PsiMode,
+    pub warn_metric: PsiMetric,
+    pub kill_metric: PsiMetric,
+    pub hold: KillHold,
+    pub rearm_max_percent: Option<f32>,
 }
 
 impl PsiConfigParsed {
     pub fn try_from_config(config: PsiConfig, global_interval: u64) -> Result<Self, PsiError> {
-        if let Some(warn) = config.warn_max_percent {
-            if warn < 0.0 || warn > 100.0 {
-                return Err(PsiError::ValidationError(format!(
-                    "PSI warn_max_percent must be between 0-100, got {}",
-                    warn
-                )));
-            }
+        if let Some(warn) = config.warn_max_percent
+            && (!(0.0..=100.0).contains(&warn))
+        {
+            return Err(PsiError::ValidationError(format!(
+                "PSI warn_max_percent must be between 0-100, got {}",
+                warn
+            )));
         }
-        if let Some(kill) = config.kill_max_percent {
-            if kill < 0.0 || kill > 100.0 {
-                return Err(PsiError::ValidationError(format!(
-                    "PSI warn_max_percent must be between 0-100, got {}",
-                    kill
-                )));
-            }
+        if let Some(kill) = config.kill_max_percent
+            && (!(0.0..=100.0).contains(&kill))
+        {
+            return Err(PsiError::ValidationError(format!(
+                "PSI kill_max_percent must be between 0-100, got {}",
+                kill
+            )));
+        }
+
+        if let Some(rearm) = config.rearm_max_percent
+            && (!(0.0..=100.0).contains(&rearm))
+        {
+            return Err(PsiError::ValidationError(format!(
+                "PSI rearm_max_percent must be between 0-100, got {}",
+                rearm
+            )));
         }
 
         if config.kill_max_percent.is_some() && config.amount_to_free.is_none() {
@@ -136,7 +304,7 @@ impl PsiConfigParsed {
             .check_interval_ms
             .unwrap_or_else(|| std::cmp::min(global_interval * 10, 300_000));
 
-        if check_interval_ms < 100 || check_interval_ms > 300000 {
+        if !(100..=300000).contains(&check_interval_ms) {
             return Err(PsiError::ValidationError(format!(
                 "PSI check_interval_ms must be between 100 and 300000, got {}",
                 check_interval_ms
@@ -146,13 +314,616 @@ impl PsiConfigParsed {
         Ok(Self {
             warn_max_percent: config.warn_max_percent,
             kill_max_percent: config.kill_max_percent,
-            amount_to_free: amount_to_free,
+            amount_to_free,
             check_interval_ms,
+            mode: config.mode.unwrap_or(PsiMode::Trigger),
+            warn_metric: PsiMetric::from_config(config.warn_line, config.warn_window),
+            kill_metric: PsiMetric::from_config(config.kill_line, config.kill_window),
+            hold: KillHold::new(config.kill_after_samples, config.kill_after_ms),
+            rearm_max_percent: config.rearm_max_percent,
         })
     }
+
+    /// The metric reported in the heartbeat: the kill metric if a kill threshold is set.
+    pub fn primary_metric(&self) -> PsiMetric {
+        if self.kill_max_percent.is_some() {
+            self.kill_metric
+        } else {
+            self.warn_metric
+        }
+    }
+
+    /// The window kernel triggers actually use when `check_interval_ms` lies outside the
+    /// kernel's limits. None if no trigger is registered or the interval fits.
+    pub fn clamped_trigger_window(&self) -> Option<u64> {
+        let (kill, warn, interval_ms) = wanted_triggers(self);
+        let window_ms = trigger_window_ms(interval_ms);
+        (self.mode == PsiMode::Trigger
+            && (kill.is_some() || warn.is_some())
+            && window_ms != interval_ms)
+            .then_some(window_ms)
+    }
+
+    fn uses_full_line(&self) -> bool {
+        (self.kill_max_percent.is_some() && self.kill_metric.line == PsiLine::Full)
+            || (self.warn_max_percent.is_some() && self.warn_metric.line == PsiLine::Full)
+    }
 }
 
-pub fn validate_psi_availability() -> Result<(), PsiError> {
-    read_psi_total()?;
+pub fn validate_psi_availability(config: &PsiConfigParsed, path: &Path) -> Result<(), PsiError> {
+    let snapshot = read_psi(path)?;
+    if config.uses_full_line() && snapshot.full.is_none() {
+        return Err(PsiError::FieldNotFound("full"));
+    }
     Ok(())
 }
+
+/// A registered kernel PSI trigger.
+/// The kernel raises POLLPRI on the fd whenever the stall time of the given line within
+/// the window exceeds the threshold (at most once per window).
+struct PsiTrigger {
+    file: File,
+}
+
+impl PsiTrigger {
+    fn register(
+        path: &Path,
+        line: PsiLine,
+        percent: f32,
+        window_ms: u64,
+    ) -> Result<Self, PsiError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open(path)?;
+
+        file.write_all(trigger_spec(line, percent, window_ms).as_bytes())?;
+
+        Ok(Self { file })
+    }
+
+    /// Tries the preferred window first, then the nearest window unprivileged users may use.
+    fn register_with_fallback(
+        path: &Path,
+        line: PsiLine,
+        percent: f32,
+        window_ms: u64,
+    ) -> Result<Self, PsiError> {
+        match Self::register(path, line, percent, window_ms) {
+            Ok(t) => Ok(t),
+            Err(e) => match unprivileged_window_ms(window_ms) {
+                Some(rounded) => Self::register(path, line, percent, rounded),
+                None => Err(e),
+            },
+        }
+    }
+}
+
+// What to write to the pressure file: "<line> <stall us> <window us>", stall at least 1us
+fn trigger_spec(line: PsiLine, percent: f32, window_ms: u64) -> String {
+    let window_us = window_ms * 1000;
+    let stall_us = ((percent as f64 / 100.0) * window_us as f64) as u64;
+    let stall_us = stall_us.clamp(1, window_us);
+    // The kernel expects the NUL terminator to be part of the write
+    format!("{} {} {}\0", line.as_str(), stall_us, window_us)
+}
+
+// The trigger window for a sampling interval, within the kernel's limits
+fn trigger_window_ms(interval_ms: u64) -> u64 {
+    interval_ms.clamp(TRIGGER_WINDOW_MIN_MS, TRIGGER_WINDOW_MAX_MS)
+}
+
+// The nearest window unprivileged users may use, if `window_ms` isn't one already
+fn unprivileged_window_ms(window_ms: u64) -> Option<u64> {
+    let step = TRIGGER_WINDOW_UNPRIVILEGED_STEP_MS;
+    let rounded = window_ms.div_ceil(step) * step;
+    let rounded = rounded.min(TRIGGER_WINDOW_MAX_MS / step * step);
+    (rounded != window_ms).then_some(rounded)
+}
+
+// (kill threshold, warn threshold, window). Thresholds on kernel averages get no trigger.
+type TriggerSpec = (Option<(PsiLine, f32)>, Option<(PsiLine, f32)>, u64);
+
+fn wanted_triggers(c: &PsiConfigParsed) -> TriggerSpec {
+    let kill = c
+        .kill_max_percent
+        .filter(|_| c.kill_metric.window == PsiWindow::Total)
+        .map(|p| (c.kill_metric.line, p));
+    let warn = c
+        .warn_max_percent
+        .filter(|_| c.warn_metric.window == PsiWindow::Total)
+        .map(|p| (c.warn_metric.line, p));
+    (kill, warn, c.check_interval_ms)
+}
+
+/// Kernel PSI triggers for the kill and warn thresholds, re-armed whenever the
+/// PSI configuration changes (startup, hot-reload).
+struct PsiTriggers {
+    path: PathBuf,
+    armed_for: Option<TriggerSpec>,
+    kill: Option<PsiTrigger>,
+    warn: Option<PsiTrigger>,
+    kill_fired: bool,
+    warn_fired: bool,
+}
+
+impl PsiTriggers {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            armed_for: None,
+            kill: None,
+            warn: None,
+            kill_fired: false,
+            warn_fired: false,
+        }
+    }
+
+    /// Registers triggers matching `config`, or drops them if PSI triggers are not wanted.
+    /// On failure (e.g. unprivileged on older kernels), logs once and leaves the
+    /// caller on the delta-sampling path.
+    fn sync(&mut self, config: Option<&PsiConfigParsed>) {
+        let wanted = config
+            .filter(|c| c.mode == PsiMode::Trigger)
+            .map(wanted_triggers);
+        if wanted == self.armed_for {
+            return;
+        }
+
+        self.armed_for = wanted;
+        self.kill = None;
+        self.warn = None;
+        self.kill_fired = false;
+        self.warn_fired = false;
+
+        let Some((kill, warn, interval_ms)) = wanted else {
+            return;
+        };
+        let window_ms = trigger_window_ms(interval_ms);
+        if window_ms != interval_ms {
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Warn,
+                text: format!(
+                    "PSI triggers on {:?} measure over {}ms: checkIntervalMs {} is outside the kernel's {}-{}ms trigger window. Use mode 'sample' to measure over the full interval.",
+                    self.path, window_ms, interval_ms, TRIGGER_WINDOW_MIN_MS, TRIGGER_WINDOW_MAX_MS
+                ),
+            });
+        }
+
+        let result = (|| -> Result<(), PsiError> {
+            if let Some((line, p)) = kill {
+                self.kill = Some(PsiTrigger::register_with_fallback(
+                    &self.path, line, p, window_ms,
+                )?);
+            }
+            if let Some((line, p)) = warn {
+                self.warn = Some(PsiTrigger::register_with_fallback(
+                    &self.path, line, p, window_ms,
+                )?);
+            }
+            Ok(())
+        })();
+
+        if let Err(e) = result {
+            self.kill = None;
+            self.warn = None;
+            logging::emit(&SentinelEvent::Message {
+                level: LogLevel::Warn,
+                text: format!(
+                    "PSI triggers on {:?} unavailable ({}). Falling back to sampling every {}ms.",
+                    self.path, e, interval_ms
+                ),
+            });
+        }
+    }
+
+    fn kill_armed(&self) -> bool {
+        self.kill.is_some()
+    }
+
+    fn warn_armed(&self) -> bool {
+        self.warn.is_some()
+    }
+
+    fn take_kill_fired(&mut self) -> bool {
+        std::mem::take(&mut self.kill_fired)
+    }
+
+    fn take_warn_fired(&mut self) -> bool {
+        std::mem::take(&mut self.warn_fired)
+    }
+
+    fn disarm_on_error(&mut self) {
+        if !self.kill_armed() && !self.warn_armed() {
+            return;
+        }
+        // The pressure file went away under us (e.g. cgroup removed): stop relying on triggers
+        logging::emit(&SentinelEvent::Message {
+            level: LogLevel::Warn,
+            text: format!(
+                "PSI trigger on {:?} failed. Falling back to sampling.",
+                self.path
+            ),
+        });
+        self.kill = None;
+        self.warn = None;
+    }
+}
+
+/// Outcome of evaluating one pressure file against its thresholds.
+pub enum PsiVerdict {
+    Normal,
+    Warn {
+        pressure: f64,
+        threshold: f32,
+        metric: PsiMetric,
+    },
+    Kill {
+        pressure: f64,
+        threshold: f32,
+        metric: PsiMetric,
+    },
+}
+
+/// Tracks one pressure file: kernel triggers (if armed) report breaches immediately,
+/// while sampling keeps `pressure` current and serves thresholds without a trigger.
+pub struct PsiSource {
+    path: PathBuf,
+    triggers: PsiTriggers,
+    last: Option<PsiSnapshot>,
+    last_time: Instant,
+    pub pressure: Option<f64>,
+    pub metric: Option<PsiMetric>,
+    /// Kill-metric pressure from a sample taken by the latest `evaluate`, None if it took none.
+    /// Lets callers track how long a breach lasts, even while a kernel trigger serves the kill.
+    pub kill_pressure: Option<f64>,
+}
+
+impl PsiSource {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            triggers: PsiTriggers::new(path.clone()),
+            last: read_psi(&path).ok(),
+            last_time: Instant::now(),
+            path,
+            pressure: None,
+            metric: None,
+            kill_pressure: None,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn evaluate(&mut self, config: Option<&PsiConfigParsed>, now: Instant) -> PsiVerdict {
+        self.triggers.sync(config);
+        self.kill_pressure = None;
+        let Some(config) = config else {
+            return PsiVerdict::Normal;
+        };
+
+        let mut verdict = PsiVerdict::Normal;
+
+        // The kernel only tells us the threshold was crossed within its window, not by how
+        // much: report the line's 10s average, the closest figure it keeps
+        let kill_fired = self.triggers.take_kill_fired();
+        let warn_fired = self.triggers.take_warn_fired();
+        let fired = (kill_fired || warn_fired)
+            .then(|| read_psi(&self.path).ok())
+            .flatten();
+        let averaged = |line: PsiLine| -> Option<(f64, PsiMetric)> {
+            let stats = fired.as_ref()?.line(line)?;
+            let metric = PsiMetric {
+                line,
+                window: PsiWindow::Avg10,
+            };
+            Some((stats.avg10, metric))
+        };
+        if kill_fired
+            && let Some(kill_max) = config.kill_max_percent
+            && let Some((pressure, metric)) = averaged(config.kill_metric.line)
+        {
+            return PsiVerdict::Kill {
+                pressure,
+                threshold: kill_max,
+                metric,
+            };
+        }
+        if warn_fired
+            && let Some(warn_max) = config.warn_max_percent
+            && let Some((pressure, metric)) = averaged(config.warn_metric.line)
+        {
+            verdict = PsiVerdict::Warn {
+                pressure,
+                threshold: warn_max,
+                metric,
+            };
+        }
+
+        if (now.duration_since(self.last_time).as_millis() as u64) < config.check_interval_ms {
+            return verdict;
+        }
+        let Ok(current) = read_psi(&self.path) else {
+            return verdict;
+        };
+        // We need previous data to calculate pressure from totals
+        let Some(last) = self.last.replace(current) else {
+            self.last_time = now;
+            return verdict;
+        };
+        let time_delta_us = now.duration_since(self.last_time).as_micros() as f64;
+        self.last_time = now;
+
+        let pressure_of = |metric: PsiMetric| -> Option<f64> {
+            let cur = current.line(metric.line)?;
+            Some(match metric.window {
+                PsiWindow::Total => {
+                    let prev = last.line(metric.line)?;
+                    let total_delta = cur.total.saturating_sub(prev.total) as f64;
+                    if time_delta_us > 0.0 {
+                        (total_delta / time_delta_us) * 100.0
+                    } else {
+                        0.0
+                    }
+                }
+                PsiWindow::Avg10 => cur.avg10,
+                PsiWindow::Avg60 => cur.avg60,
+                PsiWindow::Avg300 => cur.avg300,
+            })
+        };
+
+        let primary = config.primary_metric();
+        self.pressure = pressure_of(primary);
+        self.metric = Some(primary);
+        self.kill_pressure = config
+            .kill_max_percent
+            .and_then(|_| pressure_of(config.kill_metric));
+
+        // Check Kill
+        if let Some(kill_max) = config.kill_max_percent
+            && !self.triggers.kill_armed()
+            && let Some(pressure) = pressure_of(config.kill_metric)
+            && pressure as f32 > kill_max
+        {
+            return PsiVerdict::Kill {
+                pressure,
+                threshold: kill_max,
+                metric: config.kill_metric,
+            };
+        }
+
+        // Check Warn
+        if matches!(verdict, PsiVerdict::Normal)
+            && let Some(warn_max) = config.warn_max_percent
+            && !self.triggers.warn_armed()
+            && let Some(pressure) = pressure_of(config.warn_metric)
+            && pressure as f32 > warn_max
+        {
+            verdict = PsiVerdict::Warn {
+                pressure,
+                threshold: warn_max,
+                metric: config.warn_metric,
+            };
+        }
+
+        verdict
+    }
+}
+
+/// The pollfd array for `wait_for_triggers`, kept by the monitor so waiting doesn't allocate.
+/// Refilled on every wait (triggers come and go with reloads and errors); the buffers only grow
+/// when more triggers are armed than ever before.
+pub struct PollSet {
+    fds: Vec<libc::pollfd>,
+    owners: Vec<(usize, bool)>, // (source index, is kill trigger) for each trigger fd
+}
+
+impl PollSet {
+    pub fn new() -> Self {
+        // The system trigger pair plus the wake fd; cgroup triggers grow it once
+        Self {
+            fds: Vec::with_capacity(3),
+            owners: Vec::with_capacity(2),
+        }
+    }
+}
+
+/// Sleeps for up to `timeout`, returning early as soon as any armed trigger fires or `wake` is
+/// readable. Sources are `system` (index 0) followed by `cgroups`.
+/// Returns true if a trigger fired (or errored and was disarmed).
+pub fn wait_for_triggers(
+    poll: &mut PollSet,
+    system: &mut PsiSource,
+    cgroups: &mut [PsiSource],
+    wake: Option<BorrowedFd<'_>>,
+    timeout: Duration,
+) -> bool {
+    let PollSet { fds, owners } = poll;
+    fds.clear();
+    owners.clear();
+    for (i, source) in std::iter::once(&*system).chain(cgroups.iter()).enumerate() {
+        let triggers = &source.triggers;
+        for (is_kill, trigger) in [(true, &triggers.kill), (false, &triggers.warn)] {
+            if let Some(t) = trigger {
+                owners.push((i, is_kill));
+                fds.push(libc::pollfd {
+                    fd: t.file.as_raw_fd(),
+                    events: libc::POLLPRI,
+                    revents: 0,
+                });
+            }
+        }
+    }
+    // Any other fd (e.g. the control socket) that should end the wait when readable
+    if let Some(fd) = wake {
+        fds.push(libc::pollfd {
+            fd: fd.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        });
+    }
+    if fds.is_empty() {
+        thread::sleep(timeout);
+        return false;
+    }
+
+    let timeout_ms = timeout.as_millis().min(libc::c_int::MAX as u128) as libc::c_int;
+    // EINTR (shutdown/reload signals) simply ends the wait early
+    let res = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, timeout_ms) };
+    if res <= 0 {
+        return false;
+    }
+
+    let mut fired = false;
+    for (pfd, &(i, is_kill)) in fds.iter().zip(owners.iter()) {
+        let source = match i {
+            0 => &mut *system,
+            _ => &mut cgroups[i - 1],
+        };
+        let triggers = &mut source.triggers;
+        if pfd.revents & libc::POLLERR != 0 {
+            triggers.disarm_on_error();
+            fired = true;
+        } else if pfd.revents & libc::POLLPRI != 0 {
+            if is_kill {
+                triggers.kill_fired = true;
+            } else {
+                triggers.warn_fired = true;
+            }
+            fired = true;
+        }
+    }
+    fired
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_spec_scales_percent_to_window() {
+        assert_eq!(
+            trigger_spec(PsiLine::Some, 10.0, 1000),
+            "some 100000 1000000\0"
+        );
+        assert_eq!(
+            trigger_spec(PsiLine::Full, 2.5, 2000),
+            "full 50000 2000000\0"
+        );
+        // Stall time stays within 1us..=window
+        assert_eq!(trigger_spec(PsiLine::Some, 0.0, 500), "some 1 500000\0");
+        assert_eq!(
+            trigger_spec(PsiLine::Some, 100.0, 500),
+            "some 500000 500000\0"
+        );
+    }
+
+    #[test]
+    fn trigger_window_is_clamped_to_kernel_limits() {
+        assert_eq!(trigger_window_ms(100), TRIGGER_WINDOW_MIN_MS);
+        assert_eq!(trigger_window_ms(1500), 1500);
+        assert_eq!(trigger_window_ms(300_000), TRIGGER_WINDOW_MAX_MS);
+    }
+
+    #[test]
+    fn unprivileged_window_rounds_up_to_whole_steps() {
+        assert_eq!(unprivileged_window_ms(500), Some(2000));
+        assert_eq!(unprivileged_window_ms(2001), Some(4000));
+        assert_eq!(unprivileged_window_ms(9500), Some(10_000));
+        assert_eq!(unprivileged_window_ms(4000), None);
+        assert_eq!(unprivileged_window_ms(TRIGGER_WINDOW_MAX_MS), None);
+    }
+
+    fn parsed(json: &str) -> PsiConfigParsed {
+        PsiConfigParsed::try_from_config(serde_json::from_str(json).unwrap(), 1000).unwrap()
+    }
+
+    #[test]
+    fn reports_clamped_trigger_window() {
+        // Defaults to 10x the global interval: 10s, the longest window the kernel takes
+        assert_eq!(
+            parsed(r#"{"warnMaxPercent": 5}"#).clamped_trigger_window(),
+            None
+        );
+        assert_eq!(
+            parsed(r#"{"warnMaxPercent": 5, "checkIntervalMs": 60000}"#).clamped_trigger_window(),
+            Some(TRIGGER_WINDOW_MAX_MS)
+        );
+        // Sampling, or only kernel averages: no trigger to clamp
+        assert_eq!(
+            parsed(r#"{"warnMaxPercent": 5, "checkIntervalMs": 60000, "mode": "sample"}"#)
+                .clamped_trigger_window(),
+            None
+        );
+        assert_eq!(
+            parsed(r#"{"warnMaxPercent": 5, "checkIntervalMs": 60000, "warnWindow": "avg60"}"#)
+                .clamped_trigger_window(),
+            None
+        );
+    }
+
+    #[test]
+    fn kill_percent_error_names_its_field() {
+        let config = serde_json::from_str(r#"{"killMaxPercent": 150, "amountToFree": "1MB"}"#);
+        let error = PsiConfigParsed::try_from_config(config.unwrap(), 1000).unwrap_err();
+        assert!(error.to_string().contains("kill_max_percent"));
+    }
+
+    #[test]
+    fn parses_some_and_full_lines() {
+        let content = "some avg10=1.50 avg60=0.75 avg300=0.10 total=123456\n\
+                       full avg10=0.50 avg60=0.25 avg300=0.00 total=6543\n";
+        let snapshot = parse_psi(content).unwrap();
+        let some = snapshot.line(PsiLine::Some).unwrap();
+        assert_eq!((some.avg10, some.avg60, some.total), (1.5, 0.75, 123456));
+        let full = snapshot.line(PsiLine::Full).unwrap();
+        assert_eq!((full.avg300, full.total), (0.0, 6543));
+    }
+
+    #[test]
+    fn full_line_is_optional_some_is_not() {
+        let snapshot = parse_psi("some avg10=0.00 avg60=0.00 avg300=0.00 total=0\n").unwrap();
+        assert!(snapshot.line(PsiLine::Full).is_none());
+        assert!(matches!(
+            parse_psi("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"),
+            Err(PsiError::FieldNotFound("some"))
+        ));
+        assert!(matches!(
+            parse_psi("some avg10=0.00 avg60=0.00 avg300=0.00\n"),
+            Err(PsiError::FieldNotFound("total="))
+        ));
+    }
+
+    #[test]
+    fn metrics_default_to_some_total() {
+        let config = parsed(r#"{"warnMaxPercent": 5, "killLine": "full"}"#);
+        let some_total = PsiMetric {
+            line: PsiLine::Some,
+            window: PsiWindow::Total,
+        };
+        assert_eq!(config.warn_metric, some_total);
+        assert_eq!(config.primary_metric(), some_total);
+        // A full kill metric without a kill threshold doesn't need the full line
+        assert!(!config.uses_full_line());
+
+        let config = parsed(
+            r#"{"killMaxPercent": 20, "amountToFree": "1MB", "killLine": "full", "killWindow": "avg60"}"#,
+        );
+        assert_eq!(config.primary_metric(), config.kill_metric);
+        assert_eq!(config.kill_metric.window, PsiWindow::Avg60);
+        assert!(config.uses_full_line());
+    }
+
+    #[test]
+    fn triggers_only_for_total_window() {
+        let config = parsed(
+            r#"{"warnMaxPercent": 5, "warnLine": "full", "killMaxPercent": 20, "amountToFree": "1MB", "killWindow": "avg10", "checkIntervalMs": 2000}"#,
+        );
+        assert_eq!(
+            wanted_triggers(&config),
+            (None, Some((PsiLine::Full, 5.0)), 2000)
+        );
+    }
+}
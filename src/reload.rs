@@ -0,0 +1,87 @@
+use crate::config::{Config, RuntimeContext};
+use crate::events::SentinelEvent;
+use crate::logging;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Detects on-disk changes of the loaded config file by comparing its mtime once per tick.
+/// A single stat() per interval is cheaper than keeping an inotify watch alive and
+/// survives editors that replace the file via rename.
+pub struct ConfigWatcher {
+    path: Option<PathBuf>,
+    last_modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    pub fn new(ctx: &RuntimeContext) -> Self {
+        let mut watcher = Self {
+            path: None,
+            last_modified: None,
+        };
+        watcher.rebind(ctx);
+        watcher
+    }
+
+    pub fn path(&self) -> Option<&PathBuf> {
+        self.path.as_ref()
+    }
+
+    /// Re-targets the watcher after a reload (the resolved file may have changed)
+    /// and marks the current state as seen.
+    pub fn rebind(&mut self, ctx: &RuntimeContext) {
+        self.path = ctx.source_path.clone();
+        self.last_modified = self.read_mtime();
+    }
+
+    pub fn has_changed(&mut self) -> bool {
+        // A missing file (e.g. mid-rename) is not a change; wait for it to reappear.
+        let Some(mtime) = self.read_mtime() else {
+            return false;
+        };
+        if self.last_modified == Some(mtime) {
+            return false;
+        }
+        self.last_modified = Some(mtime);
+        true
+    }
+
+    fn read_mtime(&self) -> Option<SystemTime> {
+        let path = self.path.as_ref()?;
+        fs::metadata(path).and_then(|m| m.modified()).ok()
+    }
+}
+
+/// Re-runs the full load/validate/compile pipeline.
+/// Returns the new context only if it is valid; on failure the caller keeps the old one.
+pub fn reload(
+    current: &RuntimeContext,
+    cli_config_path: Option<PathBuf>,
+    trigger: &str,
+) -> Option<RuntimeContext> {
+    // Without --config, discovery falls back to the defaults once the running file is gone
+    // (deleted, or renamed away mid-save). Reload that file instead, so it fails like an
+    // explicit path would and the running config stays.
+    let config_path = match (cli_config_path, &current.source_path) {
+        (None, Some(running)) => Some(Config::discover().unwrap_or_else(|| running.clone())),
+        (path, _) => path,
+    };
+    match Config::load(config_path) {
+        Ok(new_ctx) => {
+            logging::emit(&SentinelEvent::ConfigReloaded {
+                trigger: trigger.to_string(),
+                path: new_ctx.source_path.clone(),
+                changed_fields: current.source.changed_fields(&new_ctx.source),
+            });
+            Some(new_ctx)
+        }
+        Err(e) => {
+            logging::emit(&SentinelEvent::ConfigReloadFailed {
+                trigger: trigger.to_string(),
+                path: current.source_path.clone(),
+                error: e.to_string(),
+            });
+            None
+        }
+    }
+}
@@ -0,0 +1,298 @@
+use crate::meminfo::{MemInfo, MemInfoReader, parse_number, read_from_start};
+use crate::utils::get_total_swap;
+use serde::Serialize;
+use std::fmt::Write;
+use std::fs::File;
+use std::io;
+
+pub const SWAPS_PATH: &str = "/proc/swaps";
+
+// One line per swap device; a handful of devices fit comfortably
+const SWAPS_BUFFER_SIZE: usize = 4 * 1024;
+const MM_STAT_BUFFER_SIZE: usize = 256;
+
+// RAM cost per stored byte assumed for a zram device that holds too little to tell
+// (2:1 compression); below ZRAM_MIN_STORED, fixed overhead dominates the measured ratio
+const DEFAULT_ZRAM_COST: f64 = 0.5;
+const ZRAM_MIN_STORED: u64 = 16 * 1024 * 1024;
+
+/// Swap as it matters for memory pressure. Swapping a page out to zram or zswap doesn't free
+/// its RAM, only the difference to its compressed size, so free swap on a zram device is only
+/// worth `1 - compressed/original` of itself. Without zram, `free`/`total` equal SwapFree/SwapTotal.
+#[derive(Debug, Default, Clone, Copy, Serialize)]
+pub struct SwapUsage {
+    pub free: u64, // Effective: how much RAM swapping out could still relieve
+    pub total: u64,
+    pub raw_free: u64, // SwapFree/SwapTotal as the kernel reports them
+    pub raw_total: u64,
+    pub zram_stored: u64, // Uncompressed data held by zram swap devices (mm_stat orig_data_size)
+    pub zram_physical: u64, // RAM those devices use (mm_stat mem_used_total)
+    pub zswap_stored: Option<u64>, // /proc/meminfo Zswapped
+    pub zswap_physical: Option<u64>, // /proc/meminfo Zswap
+}
+
+impl SwapUsage {
+    /// SwapFree/SwapTotal taken at face value.
+    pub fn unadjusted(info: &MemInfo) -> Self {
+        Self {
+            free: info.swap_free,
+            total: info.swap_total,
+            raw_free: info.swap_free,
+            raw_total: info.swap_total,
+            zswap_stored: info.zswapped,
+            zswap_physical: info.zswap,
+            ..Default::default()
+        }
+    }
+
+    /// RAM taken by compressed swapped-out data (zram devices and the zswap pool).
+    pub fn compressed_physical(&self) -> u64 {
+        self.zram_physical + self.zswap_physical.unwrap_or(0)
+    }
+
+    /// Uncompressed size of that data.
+    pub fn compressed_stored(&self) -> u64 {
+        self.zram_stored + self.zswap_stored.unwrap_or(0)
+    }
+}
+
+/// Reads /proc/swaps and each zram swap device's `mm_stat` into preallocated buffers.
+pub struct SwapReader {
+    swaps: File,
+    buffer: Vec<u8>,
+    mm_stat_buffer: Vec<u8>,
+    path_buffer: String,
+}
+
+// From /sys/block/zramN/mm_stat, in bytes
+struct ZramStat {
+    orig_data_size: u64,
+    mem_used_total: u64,
+    mem_limit: u64, // 0 = unlimited
+}
+
+impl SwapReader {
+    pub fn new() -> io::Result<Self> {
+        Ok(Self {
+            swaps: File::open(SWAPS_PATH)?,
+            buffer: vec![0; SWAPS_BUFFER_SIZE],
+            mm_stat_buffer: vec![0; MM_STAT_BUFFER_SIZE],
+            path_buffer: String::with_capacity(64),
+        })
+    }
+
+    pub fn read(&mut self, info: &MemInfo) -> io::Result<SwapUsage> {
+        let len = read_from_start(&self.swaps, &mut self.buffer)?;
+        let (path, stat_buffer) = (&mut self.path_buffer, &mut self.mm_stat_buffer);
+        Ok(swap_usage(&self.buffer[..len], info, |device| {
+            read_zram_stat(device, path, stat_buffer).ok()
+        }))
+    }
+}
+
+/// Effective total swap right now, as the monitor sees it (see `SwapUsage`): what limits on
+/// `swap` and `combined` are measured against. SwapTotal if the zram devices can't be read.
+pub fn effective_total_swap() -> u64 {
+    let Ok(info) = MemInfoReader::new().and_then(|mut r| r.read()) else {
+        return get_total_swap();
+    };
+    SwapReader::new()
+        .and_then(|mut r| r.read(&info))
+        .map_or(info.swap_total, |usage| usage.total)
+}
+
+// `swaps` is the content of /proc/swaps; `zram_stat` reads a zram device's mm_stat
+fn swap_usage(
+    swaps: &[u8],
+    info: &MemInfo,
+    mut zram_stat: impl FnMut(&str) -> Option<ZramStat>,
+) -> SwapUsage {
+    let mut usage = SwapUsage::unadjusted(info);
+    let (mut zram_total, mut zram_free) = (0u64, 0u64);
+    let (mut effective_total, mut effective_free) = (0.0, 0.0);
+
+    // "Filename Type Size Used Priority", sizes in KiB
+    for line in swaps.split(|&b| b == b'\n').skip(1) {
+        let mut columns = line
+            .split(|b| b.is_ascii_whitespace())
+            .filter(|c| !c.is_empty());
+        let (Some(name), Some(_), Some(size), Some(used)) = (
+            columns.next(),
+            columns.next(),
+            columns.next().and_then(parse_number),
+            columns.next().and_then(parse_number),
+        ) else {
+            continue;
+        };
+        let Some(device) = name
+            .strip_prefix(b"/dev/")
+            .filter(|d| d.starts_with(b"zram"))
+            .and_then(|d| std::str::from_utf8(d).ok())
+        else {
+            continue; // Disk swap: counted as reported
+        };
+        let (size, used) = (size * 1024, used * 1024);
+
+        // Unreadable stats (e.g. sysfs not mounted) fall back to the assumed ratio
+        let stat = zram_stat(device).unwrap_or(ZramStat {
+            orig_data_size: 0,
+            mem_used_total: 0,
+            mem_limit: 0,
+        });
+        usage.zram_stored += stat.orig_data_size;
+        usage.zram_physical += stat.mem_used_total;
+
+        let (total, free) = zram_effective(size, used, &stat);
+        zram_total += size;
+        zram_free += size.saturating_sub(used);
+        effective_total += total;
+        effective_free += free;
+    }
+
+    usage.total = info.swap_total.saturating_sub(zram_total) + effective_total as u64;
+    usage.free = info.swap_free.saturating_sub(zram_free) + effective_free as u64;
+    usage
+}
+
+// (total, free) of one zram device of `size` bytes with `used` in use, in bytes of RAM that
+// swapping to it can save
+fn zram_effective(size: u64, used: u64, stat: &ZramStat) -> (f64, f64) {
+    // Incompressible data plus allocator overhead can cost more than it saves
+    let cost = if stat.orig_data_size >= ZRAM_MIN_STORED {
+        (stat.mem_used_total as f64 / stat.orig_data_size as f64).min(1.0)
+    } else {
+        DEFAULT_ZRAM_COST
+    };
+    let mut free_slots = size.saturating_sub(used);
+    if stat.mem_limit > 0 {
+        // The device stops accepting pages once it hits its memory limit
+        let room = stat.mem_limit.saturating_sub(stat.mem_used_total) as f64 / cost;
+        free_slots = free_slots.min(room as u64);
+    }
+    (size as f64 * (1.0 - cost), free_slots as f64 * (1.0 - cost))
+}
+
+// Takes the reader's buffers separately: the /proc/swaps buffer is still being parsed
+fn read_zram_stat(device: &str, path: &mut String, buffer: &mut [u8]) -> io::Result<ZramStat> {
+    path.clear();
+    write!(path, "/sys/block/{}/mm_stat", device).unwrap();
+    let file = File::open(&*path)?;
+    let len = read_from_start(&file, buffer)?;
+    parse_mm_stat(&buffer[..len]).ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
+
+fn parse_mm_stat(content: &[u8]) -> Option<ZramStat> {
+    // orig_data_size compr_data_size mem_used_total mem_limit mem_used_max ...
+    let mut fields = content
+        .split(|b| b.is_ascii_whitespace())
+        .filter(|c| !c.is_empty())
+        .map(parse_number);
+    let mut next = || fields.next().flatten();
+    let orig_data_size = next()?;
+    let _compr_data_size = next()?;
+    let mem_used_total = next()?;
+    let mem_limit = next()?;
+    Some(ZramStat {
+        orig_data_size,
+        mem_used_total,
+        mem_limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{MemoryConfig, MemoryConfigParsed};
+    use crate::config_error::LimitPool;
+
+    const MIB: u64 = 1024 * 1024;
+
+    fn stat(orig_data_size: u64, mem_used_total: u64, mem_limit: u64) -> ZramStat {
+        ZramStat {
+            orig_data_size,
+            mem_used_total,
+            mem_limit,
+        }
+    }
+
+    #[test]
+    fn parses_mm_stat() {
+        let s = parse_mm_stat(b"  4096000  1024000  1228800        0  1228800      12      0  0\n")
+            .unwrap();
+        assert_eq!(s.orig_data_size, 4096000);
+        assert_eq!(s.mem_used_total, 1228800);
+        assert_eq!(s.mem_limit, 0);
+        // Older kernels have fewer columns, but never fewer than four
+        assert!(parse_mm_stat(b"1 2 3 4").is_some());
+        assert!(parse_mm_stat(b"1 2 3").is_none());
+        assert!(parse_mm_stat(b"1 2 x 4").is_none());
+    }
+
+    #[test]
+    fn small_devices_use_default_cost() {
+        let (total, free) = zram_effective(1024 * MIB, 0, &stat(MIB, MIB, 0));
+        assert_eq!(total, 512.0 * MIB as f64);
+        assert_eq!(free, 512.0 * MIB as f64);
+    }
+
+    #[test]
+    fn measured_cost_scales_savings() {
+        // 4:1 compression saves three quarters of each stored page
+        let (total, free) = zram_effective(1024 * MIB, 256 * MIB, &stat(256 * MIB, 64 * MIB, 0));
+        assert_eq!(total, 768.0 * MIB as f64);
+        assert_eq!(free, 576.0 * MIB as f64);
+    }
+
+    #[test]
+    fn incompressible_data_saves_nothing() {
+        let (total, free) = zram_effective(1024 * MIB, 64 * MIB, &stat(64 * MIB, 80 * MIB, 0));
+        assert_eq!((total, free), (0.0, 0.0));
+    }
+
+    #[test]
+    fn mem_limit_caps_free_slots() {
+        // 32M of room left at 1:4 cost fits 128M more, not the 768M of free slots
+        let (_, free) = zram_effective(1024 * MIB, 256 * MIB, &stat(256 * MIB, 64 * MIB, 96 * MIB));
+        assert_eq!(free, 96.0 * MIB as f64);
+    }
+
+    const SWAPS: &[u8] = b"Filename\tType\tSize\tUsed\tPriority
+/dev/zram0                              partition\t8388608\t0\t100
+/swapfile                               file\t\t2097152\t1048576\t-2
+";
+
+    #[test]
+    fn zram_counts_for_what_it_saves() {
+        let info = MemInfo {
+            swap_total: 10 * 1024 * MIB,
+            swap_free: 9 * 1024 * MIB,
+            ..Default::default()
+        };
+        let usage = swap_usage(SWAPS, &info, |_| None);
+        // 8G of empty zram at the assumed 2:1 saves 4G; the swap file counts as reported
+        assert_eq!(usage.total, 6 * 1024 * MIB);
+        assert_eq!(usage.free, 5 * 1024 * MIB);
+        assert_eq!(usage.raw_total, 10 * 1024 * MIB);
+    }
+
+    #[test]
+    fn swap_limit_between_effective_and_raw_total_is_rejected() {
+        let info = MemInfo {
+            swap_total: 10 * 1024 * MIB,
+            swap_free: 9 * 1024 * MIB,
+            ..Default::default()
+        };
+        let total = swap_usage(SWAPS, &info, |_| None).total;
+        let limit = |bytes: &str| {
+            let config = MemoryConfig {
+                kill_min_free_bytes: Some(bytes.to_string()),
+                ..Default::default()
+            };
+            MemoryConfigParsed::try_from_config(config, "swap", Some((total, LimitPool::Swap)))
+                .is_ok()
+        };
+        // Effective free swap never exceeds 6G, so a 7G limit would always be breached
+        assert!(!limit("7GiB"));
+        assert!(limit("5GiB"));
+    }
+}
@@ -21,6 +21,7 @@ After=graphical-session.target
 [Service]
 Type=simple
 {}
+ExecReload=/bin/kill -HUP $MAINPID
 Restart=on-failure
 RestartSec=5s
 # Unprivileged users cannot usually set negative Nice/OOMScore to run with highest priority.
@@ -1,5 +1,6 @@
 use byte_unit::Byte;
 use std::sync::OnceLock;
+use std::time::Duration;
 use sysinfo::{MemoryRefreshKind, RefreshKind, System};
 
 static TOTAL_MEMORY: OnceLock<u64> = OnceLock::new();
@@ -8,6 +9,22 @@ pub fn parse_size(s: &str) -> Option<u64> {
     Byte::parse_str(s, true).ok().map(|b| b.as_u64())
 }
 
+/// Parses "500ms", "30s", "10m", "2h" (a bare number means seconds).
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (num, unit) = s.split_at(split);
+    let n: u64 = num.parse().ok()?;
+    let ms = match unit.trim() {
+        "ms" => n,
+        "" | "s" => n.checked_mul(1000)?,
+        "m" => n.checked_mul(60_000)?,
+        "h" => n.checked_mul(3_600_000)?,
+        _ => return None,
+    };
+    Some(Duration::from_millis(ms))
+}
+
 pub fn get_total_memory() -> u64 {
     *TOTAL_MEMORY.get_or_init(|| {
         let mut sys = System::new_with_specifics(
@@ -17,3 +34,38 @@ pub fn get_total_memory() -> u64 {
         sys.total_memory()
     })
 }
+
+// Not cached: swap can be added or removed at runtime (swapon/swapoff)
+pub fn get_total_swap() -> u64 {
+    let mut sys = System::new_with_specifics(
+        RefreshKind::nothing().with_memory(MemoryRefreshKind::everything()),
+    );
+    sys.refresh_memory();
+    sys.total_swap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("10m"), Some(Duration::from_secs(600)));
+        assert_eq!(parse_duration("2h"), Some(Duration::from_secs(7200)));
+        assert_eq!(parse_duration(" 45 "), Some(Duration::from_secs(45)));
+        assert_eq!(parse_duration("5 m"), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn parse_duration_rejects_garbage() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("10d"), None);
+        assert_eq!(parse_duration("-5s"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+        assert_eq!(parse_duration("99999999999999999999h"), None);
+        assert_eq!(parse_duration(&format!("{}h", u64::MAX / 1000)), None);
+    }
+}